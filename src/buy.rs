@@ -1,11 +1,15 @@
 use crate::*;
 use crate::proposals::{ActionCall};
+use near_sdk::PromiseOrValue;
 
 #[near_bindgen]
 impl Contract {
     /// Initiate buying of an NFT. 
     /// This function will initiate a cross-contract-call, and _buy_nft_from_vault()_ will do the actual moving of the NFT, in the FonoRoot minting contract
-    pub fn buy_nft(&self, root_id: TokenId, minting_contract: AccountId) {
+    /// The attached deposit has to cover the price and the storage deposit (VAULT_STORAGE_DEPOSIT), the excess is refunded.
+    #[payable]
+    pub fn buy_nft(&mut self, root_id: TokenId, minting_contract: AccountId) {
+        self.assert_not_migrating();
         log!("buy_nft() inside DAO contract started, root_id: {}, minting_contract: {}", root_id, minting_contract);
 
         let uniq_id = UniqId::new(minting_contract.clone(), root_id.clone());
@@ -14,13 +18,255 @@ impl Contract {
             panic!("TreeIndex not found! Most likely root_id or contract is incorrect.");
         });
 
-        let price = u128::from(self.income_tables.get(&tree_index).unwrap().price.unwrap());
-        assert_eq!(
-            env::attached_deposit(),
+        let mut income_table = self.income_tables.get(&tree_index).unwrap();
+        let price = income_table.current_price(env::block_timestamp()).expect("The NFT does not have a price yet!");
+        let deposit = env::attached_deposit();
+        assert!(
+            deposit >= price + VAULT_STORAGE_DEPOSIT,
+            "Not enough deposit was attached to buy the NFT, the current price is {} and {} is needed for storage",
             price,
-            "Exact price needs to be send to buy NFT"
+            VAULT_STORAGE_DEPOSIT
         );
+        income_table.assert_on_sale(env::block_timestamp());
+        self.assert_presale_access(&income_table, tree_index, &env::predecessor_account_id());
+        income_table.pending_sales = income_table.pending_sales + 1;                        // Reserve an edition until the callback
+        self.income_tables.insert(&tree_index, &income_table);
 
+        let buyer = env::predecessor_account_id();                                          // This can be a relayer or another contract, not only the signer
+        if deposit > price + VAULT_STORAGE_DEPOSIT {                                        // The price of a dutch auction can go down until the transaction arrives
+            self.internal_send_revenue(buyer.clone(), deposit - price - VAULT_STORAGE_DEPOSIT, None);
+        }
+        let purchase_id = self.internal_start_purchase(tree_index, VAULT_STORAGE_DEPOSIT);
+        self.pending_purchase_deposits = self.pending_purchase_deposits + price + VAULT_STORAGE_DEPOSIT;
+
+        self.internal_buy_from_vault(minting_contract, root_id)
+        .then(ext_self::buy_nft_callback(
+            tree_index,
+            buyer,
+            U128(price),
+            purchase_id,
+            env::current_account_id(),
+            0,
+            Gas(50_000_000_000_000)
+        ));
+        
+        log!("Initiating cross-contract call! Function inside DAO contract exiting...");
+    }
+
+    /// Buying an NFT with a whitelisted NEP-141 token. The buyer calls `ft_transfer_call` on the token contract,
+    /// `msg` has to be a JSON-stringified FtBuyArgs object. Returns the amount of tokens that should be refunded to the buyer.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        self.assert_not_migrating();
+        let token_id = env::predecessor_account_id();
+        assert!(self.ft_whitelist.contains(&token_id), "This token is not whitelisted!");
+        let buy_args: FtBuyArgs = near_sdk::serde_json::from_str(&msg).expect("msg is not a valid FtBuyArgs object!");
+        log!("ft_on_transfer() inside DAO contract started, root_id: {}, minting_contract: {}, buyer: {}", buy_args.root_id, buy_args.minting_contract, sender_id);
+
+        let uniq_id = UniqId::new(buy_args.minting_contract.clone(), buy_args.root_id.clone());
+        let tree_index = self.uniq_id_to_tree_index.get(&uniq_id).unwrap_or_else(|| {
+            panic!("TreeIndex not found! Most likely root_id or contract is incorrect.");
+        });
+
+        let mut income_table = self.income_tables.get(&tree_index).unwrap();
+        let price = income_table.ft_prices.get(&token_id).cloned().unwrap_or_else(|| {
+            panic!("This NFT can not be bought with this token!");
+        });
+        assert!(amount.0 >= price.0, "Not enough tokens were sent to buy the NFT");         // If there is more, the difference will be refunded
+        income_table.assert_on_sale(env::block_timestamp());
+        self.assert_presale_access(&income_table, tree_index, &sender_id);
+        income_table.pending_sales = income_table.pending_sales + 1;                        // Reserve an edition until the callback
+        self.income_tables.insert(&tree_index, &income_table);
+        let purchase_id = self.internal_start_purchase(tree_index, 0);                     // The storage is paid by the DAO, because only tokens were sent
+
+        self.internal_buy_from_vault(buy_args.minting_contract, buy_args.root_id)
+        .then(ext_self::ft_buy_nft_callback(
+            tree_index,
+            sender_id,
+            token_id,
+            amount,
+            price,
+            purchase_id,
+            env::current_account_id(),
+            0,
+            Gas(50_000_000_000_000)
+        ))
+        .into()
+    }
+
+    /// Callback that will run when the NFT was successfully moved to the new owner, the callback is updating the balances in the IncomeTable
+    /// `price` is the price that was paid, it can be different from the current price, if there is a price schedule. The platform fee (see Policy) is deducted from it.
+    /// The unused part of the storage deposit is sent back to the buyer, or the whole deposit with the price, if the NFT was not moved. If a refund fails, it is saved as a FailedTransaction.
+    #[private]
+    pub fn buy_nft_callback(
+        &mut self, 
+        #[callback_result] result: Result<VaultResult, near_sdk::PromiseError>,
+        tree_index: TreeIndex,
+        buyer: AccountId,
+        price: U128,
+        purchase_id: u64
+    ) {
+        let mut the_income_table = self.income_tables.get(&tree_index).unwrap();
+        let vault_result = Self::read_vault_result(result);                                 // We will send back the money if success is false
+        self.pending_purchase_deposits = self.pending_purchase_deposits.saturating_sub(price.0 + VAULT_STORAGE_DEPOSIT);  // Purchases started before the upgrade were not counted
+        let storage_refund = self.internal_settle_purchase_storage(purchase_id, vault_result.storage_used(), vault_result.success());
+        the_income_table.pending_sales = the_income_table.pending_sales.saturating_sub(1);  // The reserved edition is either sold, or released. Purchases started before the upgrade did not reserve
+        if vault_result.success() {
+            log!("This NFT was bought: {} (TreeIndex) Buyer: {}", tree_index, buyer);
+            self.internal_record_sale(tree_index, Sale {
+                purchase_id: purchase_id,
+                buyer: buyer.clone(),
+                price: price,
+                token_id: None,
+                nft_id: vault_result.token_id(),
+                timestamp: env::block_timestamp(),
+            });
+            let fee = self.internal_platform_fee(price.0);
+            let price = price.0 - fee;                                                      // The rest of the price is the income of the song
+            self.platform_fee_balance = self.platform_fee_balance + fee;
+            self.total_platform_fees = self.total_platform_fees + fee;
+            if fee > 0 {
+                log!("Platform fee: {} yoctoNEAR", fee);
+            }
+            the_income_table.total_income = the_income_table.total_income + price;
+            the_income_table.sold = the_income_table.sold + 1;
+            let credited = self.internal_credit_revenue(&mut the_income_table, tree_index, price);   // Beneficiaries can claim their part right away
+            the_income_table.current_balance = the_income_table.current_balance + price - credited;
+            if storage_refund > 0 {
+                self.internal_send_revenue(buyer, storage_refund, None);
+            }
+        } else {
+            log!("Buying the NFT failed. Sending back {} yoctoNEAR to {}", price.0 + storage_refund, buyer);
+            self.internal_send_revenue(buyer, price.0 + storage_refund, None);
+        }
+        self.income_tables.insert(&tree_index, &the_income_table);
+    }
+
+    /// Callback that will run when the NFT that was bought with a NEP-141 token was moved to the new owner.
+    /// The platform fee is deducted from the price, and it is kept in the platform fee balance of the token.
+    /// The return value is the amount of tokens that the token contract will refund to the buyer.
+    #[private]
+    pub fn ft_buy_nft_callback(
+        &mut self,
+        #[callback_result] result: Result<VaultResult, near_sdk::PromiseError>,
+        tree_index: TreeIndex,
+        buyer: AccountId,
+        token_id: AccountId,
+        amount: U128,
+        price: U128,
+        purchase_id: u64
+    ) -> U128 {
+        let vault_result = Self::read_vault_result(result);                                 // The token contract will refund everything if success is false
+        self.internal_settle_purchase_storage(purchase_id, vault_result.storage_used(), vault_result.success());   // Nothing to refund in $NEAR, the DAO paid for the storage
+        let mut the_income_table = self.income_tables.get(&tree_index).unwrap();
+        the_income_table.pending_sales = the_income_table.pending_sales.saturating_sub(1);  // The reserved edition is either sold, or released. Purchases started before the upgrade did not reserve
+        if vault_result.success() {
+            log!("This NFT was bought: {} (TreeIndex) Buyer: {} Price: {} {}", tree_index, buyer, price.0, token_id);
+            self.internal_record_sale(tree_index, Sale {
+                purchase_id: purchase_id,
+                buyer: buyer,
+                price: price,
+                token_id: Some(token_id.clone()),
+                nft_id: vault_result.token_id(),
+                timestamp: env::block_timestamp(),
+            });
+            let fee = self.internal_platform_fee(price.0);
+            *self.ft_platform_fee_balances.entry(token_id.clone()).or_insert(0) += fee;
+            *self.ft_total_platform_fees.entry(token_id.clone()).or_insert(0) += fee;
+            if fee > 0 {
+                log!("Platform fee: {} {}", fee, token_id);
+            }
+            *the_income_table.ft_total_income.entry(token_id.clone()).or_insert(0) += price.0 - fee;
+            *the_income_table.ft_balances.entry(token_id.clone()).or_insert(0) += price.0 - fee;
+            the_income_table.sold = the_income_table.sold + 1;
+            self.income_tables.insert(&tree_index, &the_income_table);
+            U128(amount.0 - price.0)
+        } else {
+            log!("Buying the NFT failed. {} {} will be refunded to {}", amount.0, token_id, buyer);
+            self.income_tables.insert(&tree_index, &the_income_table);
+            amount
+        }
+    }
+}
+
+impl Contract {
+    /// Reads the result of `buy_nft_from_vault`.
+    /// If the call failed, the protocol sends the attached deposit back to the DAO, so no storage was used.
+    fn read_vault_result(result: Result<VaultResult, near_sdk::PromiseError>) -> VaultResult {
+        result.unwrap_or_else(|error| {
+            log!("Result: {:?}", error);
+            VaultResult::Detailed { success: false, storage_used: Some(U128(0)), token_id: None }
+        })
+    }
+
+    /// Platform fee of a sale for `price`, in the same unit as the price. See `platform_fee` in Policy
+    fn internal_platform_fee(&self, price: Balance) -> Balance {
+        price * self.policy.get().unwrap().to_policy().platform_fee as u128 / 10_000u128
+    }
+
+    /// Appends the sale to the sales log of the song, and to the purchases of the buyer
+    pub(crate) fn internal_record_sale(&mut self, tree_index: TreeIndex, sale: Sale) {
+        let mut sales = self.sales.get(&tree_index).unwrap_or_else(|| Vector::new(StorageKeys::SongSales(tree_index)));
+        let mut purchases = self.purchases_by_buyer.get(&sale.buyer).unwrap_or_else(|| {
+            Vector::new(StorageKeys::BuyerPurchases(sale.buyer.clone()))
+        });
+
+        purchases.push(&(tree_index, sales.len()));
+        self.purchases_by_buyer.insert(&sale.buyer, &purchases);
+        sales.push(&sale);
+        self.sales.insert(&tree_index, &sales);
+    }
+
+    /// Creates the storage accounting record for a new purchase. `paid_by_buyer` is the part of VAULT_STORAGE_DEPOSIT that the buyer paid for
+    pub(crate) fn internal_start_purchase(&mut self, tree_index: TreeIndex, paid_by_buyer: Balance) -> u64 {
+        let purchase_id = self.purchase_nonce;
+        self.purchase_storage.insert(&purchase_id, &PurchaseStorage {
+            tree_index: tree_index,
+            deposit: VAULT_STORAGE_DEPOSIT,
+            paid_by_buyer: paid_by_buyer,
+            used: None,
+            refunded: 0,
+            spent_by_dao: 0,
+        });
+        self.purchase_nonce = self.purchase_nonce + 1;
+        purchase_id
+    }
+
+    /// Records how much storage was used by the purchase. Returns the amount that should be refunded to the buyer.
+    /// If the NFT was not moved (`sold` is false), the buyer gets back the whole storage deposit, the used storage is paid by the DAO.
+    pub(crate) fn internal_settle_purchase_storage(&mut self, purchase_id: u64, storage_used: Option<Balance>, sold: bool) -> Balance {
+        let mut record = match self.purchase_storage.get(&purchase_id) {
+            Some(record) => record,
+            None => return 0,
+        };
+        let used = std::cmp::min(storage_used.unwrap_or(record.deposit), record.deposit);  // If it wasn't reported, we count the whole deposit as used
+        let refund = if sold {
+            std::cmp::min(record.paid_by_buyer, record.deposit - used)                     // Only what the buyer paid can be refunded to the buyer
+        } else {
+            record.paid_by_buyer                                                            // The buyer didn't get anything, so the buyer doesn't pay for the storage
+        };
+
+        record.used = Some(used);
+        record.refunded = refund;
+        record.spent_by_dao = (used + refund).saturating_sub(record.paid_by_buyer);
+        self.total_storage_spent_by_dao = self.total_storage_spent_by_dao + record.spent_by_dao;
+        self.purchase_storage.insert(&purchase_id, &record);
+
+        log!("Purchase {} used {} yoctoNEAR storage, {} is refunded, {} was paid by the DAO", purchase_id, used, refund, record.spent_by_dao);
+        refund
+    }
+
+    /// Panics if the presale of the song is going on, and `buyer` is not on the presale allowlist
+    pub(crate) fn assert_presale_access(&self, income_table: &IncomeTable, tree_index: TreeIndex, buyer: &AccountId) {
+        if income_table.in_presale(env::block_timestamp()) {
+            assert!(
+                self.presale_allowlists.get(&tree_index).map_or(false, |allowlist| allowlist.contains(buyer)),
+                "Only accounts on the allowlist can buy during the presale!"
+            );
+        }
+    }
+
+    /// Calls `buy_nft_from_vault` on the minting contract, this will move the NFT to the buyer.
+    pub(crate) fn internal_buy_from_vault(&self, minting_contract: AccountId, root_id: TokenId) -> Promise {
         let args = BuyArgs {
             root_id: root_id
         };
@@ -32,7 +278,7 @@ impl Contract {
         let action = ActionCall {
             method_name: "buy_nft_from_vault".to_string(),
             args: base64_args.into(),
-            deposit: U128(100_000_000_000_000_000_000_000),                                 // This big number is 0.1 NEAR, it is for storage. The actual price stays in the DAO contract
+            deposit: U128(VAULT_STORAGE_DEPOSIT),                                           // This is for storage. The actual price stays in the DAO contract
             gas: U64(100_000_000_000_000),
         };
 
@@ -44,36 +290,5 @@ impl Contract {
             action.deposit.0,
             Gas(action.gas.0),
         )
-        .then(ext_self::buy_nft_callback(
-            tree_index,
-            env::current_account_id(),
-            0,
-            Gas(50_000_000_000_000)
-        ));
-        
-        log!("Initiating cross-contract call! Function inside DAO contract exiting...");
-    }
-
-    /// Callback that will run when the NFT was successfully moved to the new owner, the callback is updating the balances in the IncomeTable
-    #[private]
-    pub fn buy_nft_callback(
-        &mut self, 
-        #[callback_result] result: Result<bool, near_sdk::PromiseError>,
-        tree_index: TreeIndex
-    ) {
-        let mut the_income_table = self.income_tables.get(&tree_index).unwrap();
-        let success = result.unwrap_or_else(|error| {                                       // We will send back the money if success is false
-            log!("Result: {:?}", error);
-            false
-        });
-        if success {
-            log!("This NFT was bought: {} (TreeIndex) Buyer: {}", tree_index, env::signer_account_id());
-            the_income_table.total_income = the_income_table.total_income + u128::from(the_income_table.price.unwrap());
-            the_income_table.current_balance = the_income_table.current_balance + u128::from(the_income_table.price.unwrap());
-            self.income_tables.insert(&tree_index, &the_income_table);
-        } else {
-            log!("Buying the NFT failed. Sending back money to {}", env::signer_account_id());
-            Promise::new(env::signer_account_id()).transfer(u128::from(the_income_table.price.unwrap()));
-        }
     }
 }
\ No newline at end of file
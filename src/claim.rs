@@ -0,0 +1,159 @@
+use crate::*;
+
+/// Gas for the callback after claimed revenue was sent
+const GAS_FOR_CLAIM_CALLBACK: Gas = Gas(5_000_000_000_000);
+
+impl Contract {
+    /// Splits `amount` according to the RevenueTable (and the SplitRules) of the song, and adds the parts to the claimable revenue of the beneficiaries.
+    /// Returns the amount that was credited, `amount`, or 0 if there is no RevenueTable yet.
+    /// `income_table` is updated with the split progress, the caller has to save it.
+    pub(crate) fn internal_credit_revenue(&mut self, income_table: &mut IncomeTable, tree_index: TreeIndex, amount: Balance) -> Balance {
+        let entry = match self.catalogues.get(&income_table.owner).and_then(|catalogue| catalogue.get(&tree_index)).flatten() {
+            Some(entry) => entry,
+            None => return 0,
+        };
+
+        let payout_table = match self.generate_split_payout(&entry, income_table, amount) {
+            Ok(payout_table) => payout_table,
+            Err(error) => {                                                                 // The sale is already done, so we can't panic here
+                log!("WARNING: the revenue of TreeIndex {} could not be split: {} The amount stays in the current balance of the song.", tree_index, error);
+                return 0;
+            }
+        };
+        let mut credited: Balance = 0;
+        for (beneficiary, part) in payout_table.payout.iter() {
+            let balance = self.claimable_revenue.get(beneficiary).unwrap_or(0);
+            self.claimable_revenue.insert(beneficiary, &(balance + part.0));
+            credited = credited + part.0;
+        }
+        self.total_claimable = self.total_claimable + credited;
+
+        log!("{} yoctoNEAR was credited to the beneficiaries of TreeIndex {}", credited, tree_index);
+        credited
+    }
+
+    /// Saves a new FailedTransaction, and adds it to the open failed transactions of the beneficiary
+    pub(crate) fn internal_add_failed_transaction(&mut self, failed_transaction: FailedTransaction) -> u64 {
+        let failed_id = self.failed_nonce;
+        self.failed_transactions.insert(&failed_id, &failed_transaction);
+        Self::add_to_index(
+            &mut self.open_failed_transactions,
+            &failed_transaction.beneficiary,
+            failed_id,
+            StorageKeys::BeneficiaryFailedTransactions(failed_transaction.beneficiary.clone())
+        );
+        self.failed_nonce = self.failed_nonce + 1;
+        failed_id
+    }
+
+    /// Saves the FailedTransaction with `failed_id`, and removes it from the open failed transactions, if it is Settled or WrittenOff
+    pub(crate) fn internal_update_failed_transaction(&mut self, failed_id: u64, failed_transaction: &FailedTransaction) {
+        self.failed_transactions.insert(&failed_id, failed_transaction);
+        if !failed_transaction.is_open() {
+            Self::remove_from_index(&mut self.open_failed_transactions, &failed_transaction.beneficiary, failed_id);
+        }
+    }
+
+    /// Sends the FailedTransaction with `failed_id` again, to `receiver`.
+    /// The status is Retried until the callback runs, so the same transaction can not be sent twice at the same time.
+    pub(crate) fn internal_resend_failed_transaction(&mut self, failed_id: u64, receiver: AccountId) -> Promise {
+        let mut failed_transaction = self.failed_transactions.get(&failed_id).expect("Failed transaction not found!");
+        assert_eq!(
+            failed_transaction.status,
+            FailedTransactionStatus::Pending,
+            "Only pending failed transactions can be sent again!"
+        );
+
+        failed_transaction.set_status(FailedTransactionStatus::Retried, Some(receiver.clone()));
+        self.failed_transactions.insert(&failed_id, &failed_transaction);
+        log!("Resending failed transaction {}. Sending {} (token: {:?}) to {}", failed_id, failed_transaction.amount, failed_transaction.token_id, receiver);
+
+        Self::internal_transfer(receiver.clone(), failed_transaction.amount, &failed_transaction.token_id)
+        .then(ext_self::resend_failed_transaction_callback(
+            failed_id,
+            receiver,
+            env::current_account_id(),
+            0,
+            GAS_FOR_CLAIM_CALLBACK
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Withdraw claimable revenue. If `amount` is None, the whole claimable balance is sent.
+    /// The balance is decreased before the transfer, and restored by the callback if the transfer fails.
+    pub fn claim_revenue(&mut self, amount: Option<U128>) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let balance = self.claimable_revenue.get(&account_id).unwrap_or(0);
+        let amount = amount.map(|amount| amount.0).unwrap_or(balance);
+        assert!(amount > 0, "There is nothing to claim!");
+        assert!(amount <= balance, "Claimed amount is more than the claimable balance!");
+
+        self.claimable_revenue.insert(&account_id, &(balance - amount));
+        self.total_claimable = self.total_claimable - amount;
+        log!("Sending {} yoctoNEAR claimed revenue to {}", amount, account_id);
+
+        Promise::new(account_id.clone()).transfer(amount)
+        .then(ext_self::claim_revenue_callback(
+            account_id,
+            U128(amount),
+            env::current_account_id(),
+            0,
+            GAS_FOR_CLAIM_CALLBACK
+        ))
+    }
+
+    /// Callback for `claim_revenue()`. If the transfer failed, the amount is added back to the claimable balance.
+    #[private]
+    pub fn claim_revenue_callback(
+        &mut self,
+        #[callback_result] result: Result<(), near_sdk::PromiseError>,
+        account_id: AccountId,
+        amount: U128
+    ) {
+        if result.is_err() {
+            log!("WARNING! Claim failed, {} yoctoNEAR is claimable again for {}", amount.0, account_id);
+            let balance = self.claimable_revenue.get(&account_id).unwrap_or(0);
+            self.claimable_revenue.insert(&account_id, &(balance + amount.0));
+            self.total_claimable = self.total_claimable + amount.0;
+        }
+    }
+
+    /// Send a FailedTransaction again. Only the original beneficiary can do this, and they can redirect it to `new_address`.
+    pub fn retry_failed_transaction(&mut self, failed_id: u64, new_address: Option<AccountId>) -> Promise {
+        self.assert_not_migrating();
+        let failed_transaction = self.failed_transactions.get(&failed_id).expect("Failed transaction not found!");
+        assert_eq!(
+            failed_transaction.beneficiary,
+            env::predecessor_account_id(),
+            "Only the beneficiary can retry the failed transaction!"
+        );
+
+        self.internal_resend_failed_transaction(failed_id, new_address.unwrap_or(failed_transaction.beneficiary))
+    }
+
+    /// Callback for a resent FailedTransaction. It is Settled if the transfer was successful, otherwise it is Pending again, with the same ID.
+    #[private]
+    pub fn resend_failed_transaction_callback(
+        &mut self,
+        #[callback_result] result: Result<(), near_sdk::PromiseError>,
+        failed_id: u64,
+        receiver: AccountId
+    ) {
+        let mut failed_transaction = self.failed_transactions.get(&failed_id).unwrap();
+        if result.is_err() {
+            log!("WARNING! Resending failed transaction {} to {} failed again", failed_id, receiver);
+            failed_transaction.set_status(FailedTransactionStatus::Pending, Some(receiver));   // The original beneficiary can retry again
+        } else {
+            log!("Failed transaction {} was sent to {}", failed_id, receiver);
+            failed_transaction.set_status(FailedTransactionStatus::Settled, Some(receiver));
+        }
+        self.internal_update_failed_transaction(failed_id, &failed_transaction);
+    }
+
+    /// Revenue in yoctoNEAR that the account can withdraw with `claim_revenue()`
+    pub fn get_claimable(&self, account_id: AccountId) -> U128 {
+        U128(self.claimable_revenue.get(&account_id).unwrap_or(0))
+    }
+}
@@ -0,0 +1,201 @@
+use crate::*;
+use std::ops::Bound;
+
+/// Gas that is needed to split the balances of a song, and to add the transfers to the payout queue
+pub(crate) const GAS_FOR_SONG_PAYOUT: Gas = Gas(20_000_000_000_000);
+/// Gas that is needed to send one transfer from the payout queue (`ft_transfer` + `transfer_callback`)
+pub(crate) const GAS_FOR_PAYOUT_TRANSFER: Gas = Gas(15_000_000_000_000);
+
+impl Contract {
+    /// Splits the $NEAR and NEP-141 balances of a song according to its RevenueTable (and SplitRules), and saves the IncomeTable with zero balances.
+    /// The transfers are added to the payout queue, they are sent by `internal_process_payout_queue()`, so the size of the RevenueTable is not limited by the gas of one call.
+    /// If the balances can not be split, nothing is changed, and the error is returned.
+    pub(crate) fn internal_payout_song(&mut self, tree_index: TreeIndex, mut income_table: IncomeTable, entry: &CatalogueEntry) -> Result<(), String> {
+        let balance = income_table.current_balance;
+        let payout_table = self.generate_split_payout(entry, &mut income_table, balance)?; // Will contain amounts in yoctoNEAR
+        let mut transfers: Vec<PayoutTransfer> = payout_table.payout.into_iter()
+            .map(|(beneficiary, amount)| PayoutTransfer { beneficiary, amount, token_id: None, tree_index })
+            .collect();
+
+        for (token_id, balance) in income_table.ft_balances.iter() {                        // Same for every NEP-141 token that the song was sold for
+            if *balance == 0 {
+                continue;
+            }
+            let ft_payout_table = self.internal_try_payout_object(&entry.revenue_table, *balance)?;
+            for (beneficiary, amount) in ft_payout_table.payout.into_iter() {
+                transfers.push(PayoutTransfer { beneficiary, amount, token_id: Some(token_id.clone()), tree_index });
+            }
+        }
+
+        for transfer in transfers {                                                         // Everything was split, the transfers can be queued
+            self.internal_queue_transfer(transfer);
+        }
+
+        income_table.current_balance = 0;
+        income_table.ft_balances = HashMap::new();
+        income_table.last_payout = env::block_timestamp();
+        self.income_tables.insert(&tree_index, &income_table);
+        log!("Current balance for TreeIndex {} was nulled, the transfers are in the payout queue.", tree_index);
+        Ok(())
+    }
+
+    /// Adds a transfer to the end of the payout queue. Empty transfers (rounding) are not queued
+    fn internal_queue_transfer(&mut self, transfer: PayoutTransfer) {
+        if transfer.amount.0 == 0 {
+            return;
+        }
+        if transfer.token_id.is_none() {
+            self.queued_revenue = self.queued_revenue + transfer.amount.0;
+        }
+        self.payout_queue.insert(&self.payout_queue_end, &transfer);
+        self.payout_queue_end = self.payout_queue_end + 1;
+    }
+
+    /// Sends transfers from the start of the payout queue, until the queue is empty, `max_payout_transfers` of the Policy is reached,
+    /// or there is not enough gas left for the next transfer. Returns the number of transfers that were sent.
+    pub(crate) fn internal_process_payout_queue(&mut self) -> u64 {
+        let max_transfers = self.policy.get().unwrap().to_policy().max_payout_transfers as u64;
+        let mut sent: u64 = 0;
+        while self.payout_queue_start < self.payout_queue_end && sent < max_transfers {
+            if env::prepaid_gas() - env::used_gas() < GAS_FOR_PAYOUT_TRANSFER {
+                log!("Not enough gas left for the next transfer, the rest of the payout queue can be sent with process_payout_queue().");
+                break;
+            }
+            let transfer = self.payout_queue.remove(&self.payout_queue_start).unwrap();
+            self.payout_queue_start = self.payout_queue_start + 1;
+            if transfer.token_id.is_none() {
+                self.queued_revenue = self.queued_revenue - transfer.amount.0;
+            }
+
+            log!("Sending {} (token: {:?}) to {} for TreeIndex {} ...", transfer.amount.0, transfer.token_id, transfer.beneficiary, transfer.tree_index);
+            self.internal_send_revenue(transfer.beneficiary, transfer.amount.0, transfer.token_id);  // Failed transfers are saved by transfer_callback
+            sent = sent + 1;
+        }
+        sent
+    }
+
+    /// Pays out a single song of a PayoutRevenue proposal, if `executor` is the owner of the song, or a Council member
+    fn internal_payout_for(&mut self, tree_index: TreeIndex, executor: &AccountId, is_admin: bool) -> PayoutOutcome {
+        let income_table = match self.income_tables.get(&tree_index) {
+            Some(income_table) => income_table,
+            None => return PayoutOutcome::Skipped,
+        };
+        if income_table.owner != *executor && !is_admin {                                  // RevenueTable payout happens if caller is owner or Council member
+            log!("The IncomeTable with TreeIndex {} could not be paid out, because the caller is neither the owner, nor a council member.", tree_index);
+            return PayoutOutcome::NotAuthorized;
+        }
+        if !income_table.has_balance() {
+            return PayoutOutcome::ZeroBalance;
+        }
+        let entry = match self.catalogues.get(&income_table.owner).and_then(|catalogue| catalogue.get(&tree_index)).flatten() {
+            Some(entry) => entry,
+            None => return PayoutOutcome::Skipped,
+        };
+
+        let amount = income_table.current_balance;
+        let mut ft_amounts: Vec<(AccountId, U128)> = income_table.ft_balances.iter()
+            .filter(|(_, balance)| **balance > 0)
+            .map(|(token_id, balance)| (token_id.clone(), U128(*balance)))
+            .collect();
+        ft_amounts.sort_by(|a, b| a.0.cmp(&b.0));                                           // Same order every time, the HashMap has no order
+        match self.internal_payout_song(tree_index, income_table, &entry) {
+            Ok(()) => PayoutOutcome::Paid { amount: U128(amount), ft_amounts },
+            Err(reason) => {
+                log!("The IncomeTable with TreeIndex {} could not be paid out: {}", tree_index, reason);
+                PayoutOutcome::SplitFailed { reason }
+            }
+        }
+    }
+
+    /// Pays out the songs of a PayoutRevenue proposal from `progress.cursor`, until the end of the list, or until the gas runs out.
+    /// The progress is saved for the proposal.
+    pub(crate) fn internal_run_payout(&mut self, proposal_id: u64, tree_index_list: &[TreeIndex], progress: &mut PayoutProgress) {
+        while (progress.cursor as usize) < tree_index_list.len() {
+            if env::prepaid_gas() - env::used_gas() < GAS_FOR_SONG_PAYOUT {
+                log!("Not enough gas left for the next payout, it can be continued with continue_payout_revenue({}).", proposal_id);
+                break;
+            }
+            let tree_index = tree_index_list[progress.cursor as usize];
+            let outcome = self.internal_payout_for(tree_index, &progress.executor, progress.is_admin);
+            progress.results.push((tree_index, outcome));
+            progress.cursor = progress.cursor + 1;
+        }
+
+        progress.finished = progress.cursor as usize >= tree_index_list.len();
+        self.payout_progress.insert(&proposal_id, progress);
+        self.internal_process_payout_queue();
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Continue a PayoutRevenue proposal that stopped because the gas ran out. Anyone can call this, the proposal was already approved.
+    /// Authorization is checked for the account that executed the proposal. Returns the progress.
+    pub fn continue_payout_revenue(&mut self, proposal_id: u64) -> PayoutProgress {
+        self.assert_not_migrating();
+        let mut progress = self.payout_progress.get(&proposal_id).expect("There is no payout for this proposal!");
+        assert!(!progress.finished, "The payout is already finished!");
+        let proposal: Proposal = self.proposals.get(&proposal_id).expect("ERR_NO_PROPOSAL").into();
+        let tree_index_list = match proposal.kind {
+            ProposalKind::PayoutRevenue { tree_index_list } => tree_index_list,
+            _ => unreachable!(),                                                            // Progress is only saved for PayoutRevenue
+        };
+
+        self.internal_run_payout(proposal_id, &tree_index_list, &mut progress);
+        progress
+    }
+
+    /// Pay out the songs for which the AutoPayout rule is due. At most `limit` songs are checked, starting where the previous call stopped,
+    /// and the checking stops when there is not enough gas left for the next payout. Anyone can call this function, the rules were set by the Artists.
+    /// Only the balances of the songs are paid out (see AutoPayout), the $NEAR sales are already in the claimable revenue of the beneficiaries.
+    /// Returns the list of TreeIndexes that were paid out.
+    pub fn distribute_due_revenue(&mut self, limit: u64) -> Vec<TreeIndex> {
+        self.assert_not_migrating();
+        assert!(limit > 0, "The limit has to be more than 0!");
+        let now = env::block_timestamp();
+
+        let mut paid: Vec<TreeIndex> = Vec::new();
+        let mut next_cursor = self.distribution_cursor;
+        let mut checked: u64 = 0;
+        while checked < limit {
+            if env::prepaid_gas() - env::used_gas() < GAS_FOR_SONG_PAYOUT {                // Checked before every table, so we never run out of gas in the middle of a payout
+                log!("Not enough gas left for the next payout, the rest will be paid out by the next call.");
+                break;
+            }
+            let (tree_index, income_table) = match self.income_tables.range((Bound::Included(next_cursor), Bound::Unbounded)).next() {
+                Some(next) => next,
+                None => {
+                    next_cursor = 0;                                                        // Start from the beginning, if we reach the end
+                    break;
+                }
+            };
+            checked = checked + 1;
+            next_cursor = tree_index + 1;
+            if !income_table.is_payout_due(now) {
+                continue;
+            }
+
+            let entry = match self.catalogues.get(&income_table.owner).and_then(|catalogue| catalogue.get(&tree_index)).flatten() {
+                Some(entry) => entry,
+                None => continue,                                                           // There is no RevenueTable yet, we don't know who to pay
+            };
+            log!("Automatic payout for TreeIndex {}", tree_index);
+            match self.internal_payout_song(tree_index, income_table, &entry) {
+                Ok(()) => paid.push(tree_index),
+                Err(error) => log!("The automatic payout for TreeIndex {} failed: {}", tree_index, error),
+            }
+        }
+
+        self.distribution_cursor = if next_cursor >= self.tree_index { 0 } else { next_cursor };
+        self.internal_process_payout_queue();
+        paid
+    }
+
+    /// Send the next transfers of the payout queue. Anyone can call this function, the payouts were already approved.
+    /// At most `max_payout_transfers` (see Policy) transfers are sent, and the sending stops when the gas runs out. Returns the number of transfers that were sent.
+    pub fn process_payout_queue(&mut self) -> u64 {
+        self.assert_not_migrating();
+        assert!(self.payout_queue_start < self.payout_queue_end, "The payout queue is empty!");
+        self.internal_process_payout_queue()
+    }
+}
@@ -0,0 +1,154 @@
+use crate::*;
+use near_sdk::StorageUsage;
+
+impl Contract {
+    /// Inserts (or updates) a draft, and keeps the per-artist and per-contract indexes up to date
+    pub(crate) fn internal_insert_draft(&mut self, draft: &InProgressMetadata) {
+        if let Some(old_draft) = self.in_progress_nfts.insert(&draft.id, draft) {
+            if old_draft.contract != draft.contract {                                       // UpdatePrepairedNft can move the draft to another minting contract
+                Self::remove_from_index(&mut self.drafts_by_contract, &old_draft.contract, draft.id);
+            }
+        }
+        Self::add_to_index(&mut self.drafts_by_artist, &draft.artist, draft.id, StorageKeys::ArtistDrafts(draft.artist.clone()));
+        Self::add_to_index(&mut self.drafts_by_contract, &draft.contract, draft.id, StorageKeys::ContractDrafts(draft.contract.clone()));
+    }
+
+    /// Removes a draft, and removes it from the indexes. Returns the draft, if it existed
+    pub(crate) fn internal_remove_draft(&mut self, id: u64) -> Option<InProgressMetadata> {
+        let draft = self.in_progress_nfts.remove(&id)?;
+        Self::remove_from_index(&mut self.drafts_by_artist, &draft.artist, id);
+        Self::remove_from_index(&mut self.drafts_by_contract, &draft.contract, id);
+        if draft.storage_cost > 0 {                                                         // Pay back the storage to the Artist, like remove_blob
+            Promise::new(draft.artist.clone()).transfer(draft.storage_cost);
+        }
+        Some(draft)
+    }
+
+    /// Charges the storage that was used since `initial_storage` to the draft storage balance of the Artist.
+    /// If the draft became smaller, the freed storage is credited back to the balance.
+    pub(crate) fn internal_charge_draft_storage(&mut self, id: u64, initial_storage: StorageUsage) {
+        let mut draft = self.in_progress_nfts.get(&id).expect("Draft does not exist!");
+        let balance = self.draft_storage_balances.get(&draft.artist).unwrap_or(0);
+        let final_storage = env::storage_usage();
+
+        if final_storage > initial_storage {
+            let cost = Balance::from(final_storage - initial_storage) * env::storage_byte_cost();
+            assert!(
+                balance >= cost,
+                "Not enough storage deposit for the draft! Needed: {}, available: {}. Use draft_storage_deposit()",
+                cost,
+                balance
+            );
+            self.draft_storage_balances.insert(&draft.artist, &(balance - cost));
+            self.total_draft_storage_balance = self.total_draft_storage_balance - cost;
+            draft.storage_cost = draft.storage_cost + cost;
+        } else {
+            let freed = Balance::from(initial_storage - final_storage) * env::storage_byte_cost();
+            let freed = std::cmp::min(freed, draft.storage_cost);                           // Can't give back more than what was paid for the draft
+            self.draft_storage_balances.insert(&draft.artist, &(balance + freed));
+            self.total_draft_storage_balance = self.total_draft_storage_balance + freed;
+            draft.storage_cost = draft.storage_cost - freed;
+        }
+
+        self.in_progress_nfts.insert(&id, &draft);                                           // storage_cost has fixed size, so this doesn't change the storage usage
+    }
+
+    /// Takes the draft out of the minting state, after minting failed. The draft can be minted again.
+    pub(crate) fn internal_release_draft(&mut self, id: u64) {
+        if let Some(mut draft) = self.in_progress_nfts.get(&id) {
+            draft.minting = false;
+            self.in_progress_nfts.insert(&id, &draft);
+        }
+    }
+
+    /// Restores the draft after minting failed, and fails the MintRoot proposal with `reason`, if there was one
+    pub(crate) fn internal_fail_mint(&mut self, draft_id: u64, proposal_id: Option<u64>, reason: String) {
+        self.internal_release_draft(draft_id);
+        if let Some(proposal_id) = proposal_id {
+            self.internal_finalize_mint_proposal(proposal_id, Some(reason));
+        }
+    }
+
+    /// Checks that the new RootNFT can be saved: the RootID is valid, and the song does not exist yet. Returns the UniqId of the song
+    pub(crate) fn internal_check_mint_root_result(&self, mint_root_result: &MintRootResult) -> Result<UniqId, String> {
+        let uniq_id = UniqId::try_new(mint_root_result.contract.clone(), mint_root_result.root_id.clone())?;
+        if self.income_tables.contains_key(&self.tree_index) {
+            return Err("Duplicate TreeIndex error!".to_string());
+        }
+        if self.uniq_id_to_tree_index.get(&uniq_id).is_some() {
+            return Err("The UniqId already exists!".to_string());
+        }
+        Ok(uniq_id)
+    }
+
+    pub(crate) fn add_to_index(index: &mut LookupMap<AccountId, UnorderedSet<u64>>, account: &AccountId, id: u64, prefix: StorageKeys) {
+        let mut ids = index.get(account).unwrap_or_else(|| UnorderedSet::new(prefix));
+        if ids.insert(&id) {
+            index.insert(account, &ids);
+        }
+    }
+
+    pub(crate) fn remove_from_index(index: &mut LookupMap<AccountId, UnorderedSet<u64>>, account: &AccountId, id: u64) {
+        if let Some(mut ids) = index.get(account) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                index.remove(account);                                                      // The set is empty, so it does not have anything in the storage
+            } else {
+                index.insert(account, &ids);
+            }
+        }
+    }
+
+    /// Returns the drafts from an index in paginated form
+    pub(crate) fn internal_get_drafts(&self, ids: Option<UnorderedSet<u64>>, from_index: u64, limit: u64) -> Vec<DraftOutput> {
+        match ids {
+            Some(ids) => ids
+                .iter()
+                .skip(from_index as usize)
+                .take(limit as usize)
+                .filter_map(|id| self.in_progress_nfts.get(&id))
+                .map(DraftOutput::from)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deposit for the storage of drafts. PrepareNft and UpdatePrepairedNft are paid from this balance.
+    /// Returns the new balance.
+    #[payable]
+    pub fn draft_storage_deposit(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let deposit = env::attached_deposit();
+        assert!(deposit > 0, "Attach deposit to pay for the storage of the drafts!");
+
+        let balance = self.draft_storage_balances.get(&account_id).unwrap_or(0) + deposit;
+        self.draft_storage_balances.insert(&account_id, &balance);
+        self.total_draft_storage_balance = self.total_draft_storage_balance + deposit;
+        U128(balance)
+    }
+
+    /// Withdraw the unused draft storage deposit. If `amount` is None, the whole balance is sent.
+    pub fn draft_storage_withdraw(&mut self, amount: Option<U128>) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let balance = self.draft_storage_balances.get(&account_id).unwrap_or(0);
+        let amount = amount.map(|amount| amount.0).unwrap_or(balance);
+        assert!(amount > 0, "There is nothing to withdraw!");
+        assert!(amount <= balance, "Amount is more than the draft storage balance!");
+
+        if amount == balance {
+            self.draft_storage_balances.remove(&account_id);
+        } else {
+            self.draft_storage_balances.insert(&account_id, &(balance - amount));
+        }
+        self.total_draft_storage_balance = self.total_draft_storage_balance - amount;
+        Promise::new(account_id).transfer(amount)
+    }
+
+    /// Unused draft storage deposit of the account
+    pub fn get_draft_storage_balance(&self, account_id: AccountId) -> U128 {
+        U128(self.draft_storage_balances.get(&account_id).unwrap_or(0))
+    }
+}
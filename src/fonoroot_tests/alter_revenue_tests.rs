@@ -55,7 +55,7 @@ fn alter_revenue_table_possible_to_alter_values() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
@@ -132,7 +132,7 @@ fn alter_revenue_table_not_owner_error() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
@@ -161,7 +161,7 @@ fn alter_revenue_table_not_owner_error() {
         contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
         root_id: "fono-root-1".to_string(),
     };
-    contract.mint_root_callback(Ok(second_example_result), accounts(1));
+    contract.mint_root_callback(Ok(second_example_result), accounts(1), 1, Some(id));
 
 
     // Bob is trying to alter the RevenueTable
@@ -207,7 +207,7 @@ fn alter_revenue_table_table_not_correct() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
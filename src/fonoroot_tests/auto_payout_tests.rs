@@ -0,0 +1,205 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{testing_env, AccountId, Gas};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    prepare_nft_full_proposal,
+    mint_root_proposal,
+    add_revenue_table_proposal,
+    set_auto_payout_proposal,
+    add_token_to_whitelist_proposal,
+    set_ft_price_proposal
+};
+use crate::types::{Action, AutoPayout, MintRootResult};
+use crate::Contract;
+
+
+/// Alice mints song 1 (fono-root-1) as well, with the same RevenueTable
+fn mint_second_song(context: &mut VMContextBuilder, contract: &mut Contract) {
+    let mut id = prepare_nft_full_proposal(context, contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(context, contract, 1);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "fono-root-1".to_string(),
+    }), accounts(0), 1, Some(id));
+
+    let mut unchecked_table = HashMap::default();
+    unchecked_table.insert(accounts(0), 9000);
+    unchecked_table.insert(accounts(1), 1000);
+    id = add_revenue_table_proposal(context, contract, "fono-root-1".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()), unchecked_table, U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Sets the automatic payout rule of the song, as Alice
+fn set_rule(context: &mut VMContextBuilder, contract: &mut Contract, tree_index: u64, rule: Option<AutoPayout>) {
+    let id = set_auto_payout_proposal(context, contract, tree_index, rule);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Revenue that is waiting for PayoutRevenue
+fn set_balance(contract: &mut Contract, tree_index: u64, balance: &str) {
+    let mut income_table = contract.income_tables.get(&tree_index).unwrap();
+    income_table.current_balance = to_yocto(balance);
+    contract.income_tables.insert(&tree_index, &income_table);
+}
+
+/// The song is paid out when the balance reaches the threshold
+#[test]
+fn threshold_auto_payout() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_rule(&mut context, &mut contract, 0, Some(AutoPayout { threshold: Some(U128(to_yocto("3"))), period: None }));
+
+    set_balance(&mut contract, 0, "2");
+    testing_env!(context.predecessor_account_id(accounts(3)).signer_account_id(accounts(3)).block_timestamp(5000).build());   // Danny, anyone can call this
+    assert!(contract.distribute_due_revenue(10).is_empty(), "The threshold is not reached yet.");
+
+    set_balance(&mut contract, 0, "4");
+    assert_eq!(vec![0], contract.distribute_due_revenue(10), "Song 0 should be paid out.");
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(0, income_table.current_balance, "The balance should be paid out.");
+    assert_eq!(5000, income_table.last_payout, "The time of the payout should be saved.");
+    assert_eq!(to_yocto("4"), income_table.revenue_split, "The payout should be split.");
+}
+
+/// The song is paid out when the period has passed since the last payout
+#[test]
+fn period_auto_payout() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_rule(&mut context, &mut contract, 0, Some(AutoPayout { threshold: None, period: Some(U64(1000)) }));
+    let minted_at = contract.income_tables.get(&0).unwrap().last_payout;
+
+    set_balance(&mut contract, 0, "1");
+    testing_env!(context.block_timestamp(minted_at + 999).build());
+    assert!(contract.distribute_due_revenue(10).is_empty(), "The period has not passed yet.");
+
+    testing_env!(context.block_timestamp(minted_at + 1000).build());
+    assert_eq!(vec![0], contract.distribute_due_revenue(10), "Song 0 should be paid out.");
+
+    testing_env!(context.block_timestamp(minted_at + 3000).build());
+    assert!(contract.distribute_due_revenue(10).is_empty(), "There is nothing to pay out.");
+}
+
+/// $NEAR sales are credited to the beneficiaries right away, so they don't reach the threshold. The NEP-141 revenue is paid out by the period
+#[test]
+fn auto_payout_after_sales() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_rule(&mut context, &mut contract, 0, Some(AutoPayout { threshold: Some(U128(to_yocto("1"))), period: Some(U64(1000)) }));
+    let minted_at = contract.income_tables.get(&0).unwrap().last_payout;
+    let usdc = AccountId::new_unchecked("usdc.near".to_string());
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob whitelists the token
+    let mut id = add_token_to_whitelist_proposal(&mut context, &mut contract, usdc.clone());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    testing_env!(context.predecessor_account_id(accounts(0)).signer_account_id(accounts(0)).build());     // Alice
+    id = set_ft_price_proposal(&mut context, &mut contract, 0, usdc.clone(), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Charlie buys with $NEAR
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).attached_deposit(to_yocto("5.1")).build());
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    assert_eq!(0, contract.income_tables.get(&0).unwrap().current_balance, "The price should be credited to the beneficiaries.");
+
+    testing_env!(context.attached_deposit(0).block_timestamp(minted_at + 500).build());
+    assert!(contract.distribute_due_revenue(10).is_empty(), "There is no $NEAR balance, the threshold is not reached.");
+
+    // Danny buys with USDC
+    testing_env!(context.predecessor_account_id(usdc.clone()).signer_account_id(accounts(3)).build());
+    let msg = "{\"root_id\": \"fono-root-0\", \"minting_contract\": \"minting-contract-1.near\"}".to_string();
+    contract.ft_on_transfer(accounts(3), U128(5_000_000), msg);
+    contract.ft_buy_nft_callback(Ok(true.into()), 0, accounts(3), usdc.clone(), U128(5_000_000), U128(5_000_000), 1);
+
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    assert!(contract.distribute_due_revenue(10).is_empty(), "The USDC balance does not count towards the threshold.");
+
+    testing_env!(context.block_timestamp(minted_at + 1000).build());
+    assert_eq!(vec![0], contract.distribute_due_revenue(10), "The USDC balance should be paid out after the period.");
+    assert!(contract.income_tables.get(&0).unwrap().ft_balances.is_empty(), "The USDC balance should be paid out.");
+}
+
+/// Songs without a rule are not paid out automatically
+#[test]
+fn no_auto_payout_without_rule() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    set_balance(&mut contract, 0, "100");
+    assert!(contract.distribute_due_revenue(10).is_empty(), "Song 0 does not have a rule.");
+    assert_eq!(to_yocto("100"), contract.income_tables.get(&0).unwrap().current_balance, "The balance should not change.");
+}
+
+/// The crank continues where the previous call stopped, and starts again from the beginning after the last song
+#[test]
+fn distribute_due_revenue_cursor() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    mint_second_song(&mut context, &mut contract);
+    let rule = AutoPayout { threshold: Some(U128(to_yocto("1"))), period: None };
+    set_rule(&mut context, &mut contract, 0, Some(rule.clone()));
+    set_rule(&mut context, &mut contract, 1, Some(rule));
+
+    set_balance(&mut contract, 0, "2");
+    set_balance(&mut contract, 1, "2");
+    assert_eq!(vec![0], contract.distribute_due_revenue(1), "Only song 0 should be checked.");
+    assert_eq!(1, contract.distribution_cursor, "The next call should start at song 1.");
+
+    testing_env!(context.build());
+    assert_eq!(vec![1], contract.distribute_due_revenue(1), "Song 1 should be paid out.");
+    assert_eq!(0, contract.distribution_cursor, "The next call should start from the beginning.");
+}
+
+/// When the gas runs out, the crank stops before the next table, even if the limit is higher, and the next call continues from there
+#[test]
+fn distribute_due_revenue_stops_when_gas_runs_out() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_rule(&mut context, &mut contract, 0, Some(AutoPayout { threshold: Some(U128(to_yocto("1"))), period: None }));
+    set_balance(&mut contract, 0, "2");
+
+    testing_env!(context.prepaid_gas(Gas(10_000_000_000_000)).build());
+    assert!(contract.distribute_due_revenue(1_000_000).is_empty(), "Nothing should be paid out without gas.");
+    assert_eq!(0, contract.distribution_cursor, "The cursor should not move.");
+    assert_eq!(to_yocto("2"), contract.income_tables.get(&0).unwrap().current_balance, "The balance should not change.");
+
+    testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+    assert_eq!(vec![0], contract.distribute_due_revenue(1_000_000), "Song 0 should be paid out by the next call.");
+}
+
+/// 0 would not check anything
+#[test]
+#[should_panic(expected = "The limit has to be more than 0!")]
+fn distribute_due_revenue_zero_limit_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    contract.distribute_due_revenue(0);
+}
+
+/// Only the owner can set the rule
+#[test]
+#[should_panic(expected = "Only the owner (Artist) can alter the automatic payout!")]
+fn set_auto_payout_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob
+    set_rule(&mut context, &mut contract, 0, None);
+}
+
+/// A rule without threshold and period would never be due
+#[test]
+#[should_panic(expected = "The automatic payout needs a threshold or a period!")]
+fn set_auto_payout_empty_rule_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    set_rule(&mut context, &mut contract, 0, Some(AutoPayout { threshold: None, period: None }));
+}
@@ -0,0 +1,63 @@
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, PromiseError};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song
+};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted (price is 5 NEAR), and a relayer (Danny) called `buy_nft` for Charlie, the buyer is the predecessor
+fn setup_contract_with_relayed_purchase(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(3)).attached_deposit(to_yocto("5.1")).build());
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).signer_account_id(accounts(3)).attached_deposit(0).build());
+    contract
+}
+
+/// If moving the NFT failed, the buyer is refunded, not the signer of the transaction
+#[test]
+fn failed_purchase_refunds_buyer() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_relayed_purchase(&mut context);
+
+    contract.buy_nft_callback(Ok(false.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert!(
+        get_logs().contains(&format!("Buying the NFT failed. Sending back {} yoctoNEAR to {}", to_yocto("5.1"), accounts(2))),
+        "The money should be sent back to Charlie."
+    );
+    assert_eq!(0, contract.income_tables.get(&0).unwrap().total_income, "The total income should not change.");
+}
+
+/// The buyer is saved in the sales log, even if the transaction was signed by someone else
+#[test]
+fn relayed_purchase_records_buyer() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_relayed_purchase(&mut context);
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let sales = contract.get_sales(0, 0, 10);
+    assert_eq!(accounts(2), sales[0].buyer, "Charlie should be the buyer.");
+    assert_eq!(0, contract.get_purchases_by_buyer(accounts(3), 0, 10).len(), "Danny did not buy anything.");
+}
+
+/// If the refund fails, it is saved as a FailedTransaction, so it can be sent again
+#[test]
+fn failed_refund_is_recorded() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_relayed_purchase(&mut context);
+
+    contract.buy_nft_callback(Ok(false.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    contract.transfer_callback(Err(PromiseError::Failed), accounts(2), U128(to_yocto("5")), None);
+
+    let failed = contract.get_failed_transactions(0, 10);
+    assert_eq!(1, failed.len(), "There should be 1 failed transaction.");
+    assert_eq!(accounts(2), failed[0].1.beneficiary, "The failed refund belongs to Charlie.");
+    assert_eq!(to_yocto("5"), failed[0].1.amount, "The failed refund is 5 NEAR.");
+    assert_eq!(None, failed[0].1.token_id, "The refund is in $NEAR.");
+}
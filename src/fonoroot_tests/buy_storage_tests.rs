@@ -0,0 +1,144 @@
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, PromiseError};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    add_token_to_whitelist_proposal,
+    set_ft_price_proposal
+};
+use crate::types::{Action, VaultResult, VAULT_STORAGE_DEPOSIT};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted (price is 5 NEAR), and Charlie started to buy it with `deposit`. This is purchase 0
+fn setup_contract_with_purchase(context: &mut VMContextBuilder, deposit: &str) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).attached_deposit(to_yocto(deposit)).build());
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+    contract
+}
+
+/// The buyer pays for the storage, on top of the price
+#[test]
+fn buy_nft_records_storage_deposit() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_purchase(&mut context, "6");                             // 0.9 NEAR is refunded right away
+
+    let records = contract.get_purchase_storage(0, 10);
+    assert_eq!(1, records.len(), "There should be 1 purchase.");
+    assert_eq!(VAULT_STORAGE_DEPOSIT, records[0].1.deposit, "The storage deposit should be attached to buy_nft_from_vault.");
+    assert_eq!(VAULT_STORAGE_DEPOSIT, records[0].1.paid_by_buyer, "The buyer should pay for the storage.");
+    assert_eq!(None, records[0].1.used, "The storage is not used until the callback.");
+}
+
+/// The price alone is not enough
+#[test]
+#[should_panic(expected = "Not enough deposit was attached to buy the NFT")]
+fn buy_nft_without_storage_deposit_error() {
+    let mut context = VMContextBuilder::new();
+    setup_contract_with_purchase(&mut context, "5");
+}
+
+/// If the minting contract reports the storage that was used, the rest is refunded to the buyer
+#[test]
+fn buy_nft_callback_refunds_unused_storage() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_purchase(&mut context, "5.1");
+
+    contract.buy_nft_callback(Ok(VaultResult::Detailed { success: true, storage_used: Some(U128(to_yocto("0.02"))), token_id: None }), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let record = &contract.get_purchase_storage(0, 10)[0].1;
+    assert_eq!(Some(to_yocto("0.02")), record.used, "0.02 NEAR storage should be used.");
+    assert_eq!(to_yocto("0.08"), record.refunded, "0.08 NEAR should be refunded.");
+    assert_eq!(0, record.spent_by_dao, "The DAO should not pay for the storage.");
+}
+
+/// If the minting contract doesn't report the storage, the whole deposit is counted as used
+#[test]
+fn buy_nft_callback_unreported_storage() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_purchase(&mut context, "5.1");
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let record = &contract.get_purchase_storage(0, 10)[0].1;
+    assert_eq!(Some(VAULT_STORAGE_DEPOSIT), record.used, "The whole deposit should be counted as used.");
+    assert_eq!(0, record.refunded, "Nothing should be refunded.");
+}
+
+/// If the call failed, the deposit came back to the DAO, so the whole storage deposit is refunded together with the price
+#[test]
+fn buy_nft_callback_failed_refunds_storage() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_purchase(&mut context, "5.1");
+
+    contract.buy_nft_callback(Err(PromiseError::Failed), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let record = &contract.get_purchase_storage(0, 10)[0].1;
+    assert_eq!(Some(0), record.used, "No storage should be used.");
+    assert_eq!(VAULT_STORAGE_DEPOSIT, record.refunded, "The storage deposit should be refunded.");
+    assert_eq!(0, contract.get_single_income_table(0).sold, "Nothing should be sold.");
+}
+
+/// If the minting contract could not move the NFT, the buyer gets back the price and the whole storage deposit
+#[test]
+fn buy_nft_callback_not_moved_refunds_storage() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_purchase(&mut context, "5.1");
+
+    contract.buy_nft_callback(Ok(false.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert!(
+        get_logs().contains(&format!("Buying the NFT failed. Sending back {} yoctoNEAR to {}", to_yocto("5") + VAULT_STORAGE_DEPOSIT, accounts(2))),
+        "The price and the storage deposit should be sent back to Charlie."
+    );
+    let record = &contract.get_purchase_storage(0, 10)[0].1;
+    assert_eq!(VAULT_STORAGE_DEPOSIT, record.refunded, "The storage deposit should be refunded.");
+    assert_eq!(VAULT_STORAGE_DEPOSIT, record.spent_by_dao, "The storage that the minting contract kept should be paid by the DAO.");
+}
+
+/// For purchases with NEP-141 tokens, the DAO pays for the storage
+#[test]
+fn ft_buy_nft_storage_paid_by_dao() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob whitelists the token
+    let mut id = add_token_to_whitelist_proposal(&mut context, &mut contract, AccountId::new_unchecked("usdc.near".to_string()));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    testing_env!(context.predecessor_account_id(accounts(0)).signer_account_id(accounts(0)).build());     // Alice sets the price
+    id = set_ft_price_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("usdc.near".to_string()), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("usdc.near".to_string())).signer_account_id(accounts(2)).build());
+    let msg = "{\"root_id\": \"fono-root-0\", \"minting_contract\": \"minting-contract-1.near\"}".to_string();
+    contract.ft_on_transfer(accounts(2), U128(5_000_000), msg);
+    contract.ft_buy_nft_callback(
+        Ok(VaultResult::Detailed { success: true, storage_used: Some(U128(to_yocto("0.03"))), token_id: None }),
+        0,
+        accounts(2),
+        AccountId::new_unchecked("usdc.near".to_string()),
+        U128(5_000_000),
+        U128(5_000_000),
+        0
+    );
+
+    let record = &contract.get_purchase_storage(0, 10)[0].1;
+    assert_eq!(0, record.paid_by_buyer, "The buyer did not pay for the storage.");
+    assert_eq!(to_yocto("0.03"), record.spent_by_dao, "The DAO should pay for the used storage.");
+    assert_eq!(U128(to_yocto("0.03")), contract.get_total_storage_spent_by_dao(), "The total should be 0.03 NEAR.");
+}
+
+/// The minting contract can return a bool, or an object with the storage that was used
+#[test]
+fn vault_result_json() {
+    let simple: VaultResult = near_sdk::serde_json::from_str("true").unwrap();
+    assert!(simple.success(), "The purchase should be successful.");
+    assert_eq!(None, simple.storage_used(), "The storage was not reported.");
+
+    let detailed: VaultResult = near_sdk::serde_json::from_str("{ \"success\": true, \"storage_used\": \"1000\" }").unwrap();
+    assert!(detailed.success(), "The purchase should be successful.");
+    assert_eq!(Some(1000), detailed.storage_used(), "The storage should be 1000 yoctoNEAR.");
+}
@@ -0,0 +1,78 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, PromiseError};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song
+};
+
+
+/// After a successful sale, the price is credited to the beneficiaries according to the RevenueTable
+#[test]
+fn buy_nft_credits_claimable_revenue() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert_eq!(U128(to_yocto("4.5")), contract.get_claimable(accounts(0)), "Alice should be able to claim 90% of the price.");
+    assert_eq!(U128(to_yocto("0.5")), contract.get_claimable(accounts(1)), "Bob should be able to claim 10% of the price.");
+    assert_eq!(to_yocto("5"), contract.total_claimable, "Total claimable should be equal to the price.");
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(to_yocto("5"), income_table.total_income, "Total income should be equal to the price.");
+    assert_eq!(0, income_table.current_balance, "Everything was credited, nothing should be waiting for PayoutRevenue.");
+}
+
+/// Claiming without amount will withdraw the whole claimable balance
+#[test]
+fn claim_revenue_withdraws_everything() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    contract.claim_revenue(None);
+
+    assert_eq!(U128(0), contract.get_claimable(accounts(1)), "Bob should have nothing left to claim.");
+    assert_eq!(to_yocto("4.5"), contract.total_claimable, "Only the part of Alice should be claimable.");
+}
+
+/// Can not claim more than the claimable balance
+#[test]
+#[should_panic(expected = "Claimed amount is more than the claimable balance!")]
+fn claim_revenue_too_much_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    contract.claim_revenue(Some(U128(to_yocto("1"))));
+}
+
+/// Can not claim, if there is nothing to claim
+#[test]
+#[should_panic(expected = "There is nothing to claim!")]
+fn claim_revenue_nothing_to_claim_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());                          // This is Charlie
+    contract.claim_revenue(None);
+}
+
+/// If the transfer failed, the amount can be claimed again
+#[test]
+fn claim_revenue_failed_restores_balance() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Alice
+    contract.claim_revenue(Some(U128(to_yocto("2"))));
+    assert_eq!(U128(to_yocto("2.5")), contract.get_claimable(accounts(0)), "Alice should have 2.5 NEAR left to claim.");
+
+    contract.claim_revenue_callback(Err(PromiseError::Failed), accounts(0), U128(to_yocto("2")));
+    assert_eq!(U128(to_yocto("4.5")), contract.get_claimable(accounts(0)), "The failed amount should be claimable again.");
+    assert_eq!(to_yocto("5"), contract.total_claimable, "Total claimable should be restored.");
+}
@@ -54,7 +54,7 @@ fn create_revenue_table_correct_values_are_inserted() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
@@ -114,7 +114,7 @@ fn create_revenue_table_not_owner_error() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
     
     
     // Prepare NFT
@@ -136,7 +136,7 @@ fn create_revenue_table_not_owner_error() {
         root_id: "fono-root-1".to_string(),
     };
 
-    contract.mint_root_callback(Ok(second_example_result), accounts(1));
+    contract.mint_root_callback(Ok(second_example_result), accounts(1), 1, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
@@ -185,7 +185,7 @@ fn create_revenue_table_already_exists_error() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
@@ -240,7 +240,7 @@ fn create_revenue_table_table_not_correct() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let mut unchecked_table = HashMap::default();
     unchecked_table.insert(accounts(0), 9000);
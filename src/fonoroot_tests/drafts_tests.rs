@@ -0,0 +1,295 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    create_master_group_proposal,
+    add_member_to_master_proposal,
+    prepare_nft_full_proposal,
+    prepare_nft_half_ready_proposal,
+    prepare_nft_invalid_proposal,
+    update_nft_move_to_contract_proposal,
+    update_nft_half_ready_proposal,
+    delete_prepared_nft_proposal,
+    mint_root_proposal
+};
+use crate::policy::{VersionedPolicy};
+use crate::types::{Action, DraftError, MintRootResult};
+use crate::Contract;
+use crate::Config;
+
+
+/// Creates a contract, where Alice is member of the master group of minting-contract-1.near
+fn setup_contract_with_artist(context: &mut VMContextBuilder) -> Contract {
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    let mut id = create_master_group_proposal(context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = add_member_to_master_proposal(context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    contract
+}
+
+/// Drafts can be listed for the Artist and for the minting contract, with the readiness flag
+#[test]
+fn get_drafts_by_artist_and_contract() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let drafts = contract.get_drafts_by_artist(accounts(0), 0, 10);
+    assert_eq!(2, drafts.len(), "Alice should have 2 drafts.");
+    assert!(drafts[0].ready, "The first draft should be ready.");
+    assert!(drafts[0].missing_fields.is_empty(), "The first draft should not have missing fields.");
+    assert!(!drafts[1].ready, "The second draft should not be ready.");
+    assert_eq!(
+        vec!["desc".to_string(), "meta".to_string(), "meta_hash".to_string()],
+        drafts[1].missing_fields,
+        "Description and meta should be missing from the second draft."
+    );
+
+    let by_contract = contract.get_drafts_by_contract(AccountId::new_unchecked("minting-contract-1.near".to_string()), 0, 10);
+    assert_eq!(2, by_contract.len(), "The minting contract should have 2 drafts.");
+    assert!(contract.get_drafts_by_artist(accounts(2), 0, 10).is_empty(), "Charlie should not have drafts.");
+}
+
+/// The views are paginated
+#[test]
+fn get_drafts_by_artist_paginated() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    for _ in 0..3 {
+        let id = prepare_nft_full_proposal(&mut context, &mut contract);
+        contract.act_proposal(id, Action::VoteApprove, None);
+    }
+
+    let page = contract.get_drafts_by_artist(accounts(0), 1, 1);
+    assert_eq!(1, page.len(), "The page should have 1 element.");
+    assert_eq!(1, page[0].draft.id, "The page should contain the draft with ID 1.");
+    assert_eq!(1, contract.get_drafts_by_artist(accounts(0), 2, 10).len(), "Only 1 draft should be after index 2.");
+}
+
+/// Minting removes the draft from the indexes, after the NFT was created
+#[test]
+fn mint_root_removes_draft_from_indexes() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(1, contract.get_drafts_by_artist(accounts(0), 0, 10).len(), "The draft should be listed while it is being minted.");
+
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "fono-root-0".to_string(),
+    }), accounts(0), 0, Some(id));
+
+    assert!(contract.get_drafts_by_artist(accounts(0), 0, 10).is_empty(), "Alice should not have drafts.");
+    assert!(
+        contract.get_drafts_by_contract(AccountId::new_unchecked("minting-contract-1.near".to_string()), 0, 10).is_empty(),
+        "The minting contract should not have drafts."
+    );
+}
+
+/// Updating the minting contract of the draft moves the draft to the other index
+#[test]
+fn update_draft_moves_contract_index() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Alice is added to the master group of the second minting contract as well
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-2.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-2.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Alice
+    id = update_nft_move_to_contract_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("minting-contract-2.near".to_string()));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert!(
+        contract.get_drafts_by_contract(AccountId::new_unchecked("minting-contract-1.near".to_string()), 0, 10).is_empty(),
+        "The draft should be removed from the first minting contract."
+    );
+    assert_eq!(
+        1,
+        contract.get_drafts_by_contract(AccountId::new_unchecked("minting-contract-2.near".to_string()), 0, 10).len(),
+        "The draft should be listed for the second minting contract."
+    );
+    assert_eq!(1, contract.get_drafts_by_artist(accounts(0), 0, 10).len(), "Alice should still have 1 draft.");
+}
+
+/// A complete draft does not have any problems
+#[test]
+fn validate_draft_full() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert!(contract.validate_draft(0).is_empty(), "The draft should be valid.");
+}
+
+/// Missing fields and invalid fields are listed
+#[test]
+fn validate_draft_lists_problems() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = prepare_nft_invalid_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert_eq!(
+        vec![
+            DraftError::Missing { field: "desc".to_string() },
+            DraftError::Missing { field: "meta".to_string() },
+            DraftError::Missing { field: "meta_hash".to_string() },
+        ],
+        contract.validate_draft(0),
+        "Description and meta should be missing."
+    );
+    assert_eq!(
+        vec![
+            DraftError::InvalidCid { field: "music".to_string() },
+            DraftError::InvalidUrl { field: "animation_url".to_string() },
+            DraftError::InvalidHashLength { field: "image_hash".to_string(), length: 3 },
+        ],
+        contract.validate_draft(1),
+        "Music CID, animation URL and image hash should be invalid."
+    );
+    assert!(!contract.get_draft(1).unwrap().ready, "The invalid draft should not be ready.");
+}
+
+/// MintRoot proposal can't be added for a draft that is not valid
+#[test]
+#[should_panic(expected = "The draft can not be minted")]
+fn mint_root_invalid_draft_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let id = prepare_nft_invalid_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    mint_root_proposal(&mut context, &mut contract, 0);
+}
+
+/// The draft is validated again when MintRoot is executed, because it could have been updated after the proposal was added
+#[test]
+#[should_panic(expected = "The draft can not be minted")]
+fn mint_root_draft_updated_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    let mint_id = mint_root_proposal(&mut context, &mut contract, 0);
+
+    id = update_nft_half_ready_proposal(&mut context, &mut contract, 0);                        // The metadata JSON is removed
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    contract.act_proposal(mint_id, Action::VoteApprove, None);
+}
+
+/// PrepareNft charges the storage of the draft to the draft storage balance of the Artist
+#[test]
+fn prepare_nft_charges_storage() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let id = prepare_nft_full_proposal(&mut context, &mut contract);                            // This deposits 1 NEAR
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let storage_cost = contract.get_draft(0).unwrap().draft.storage_cost;
+    assert!(storage_cost > 0, "The draft should have a storage cost.");
+    assert_eq!(
+        to_yocto("1") - storage_cost,
+        contract.get_draft_storage_balance(accounts(0)).0,
+        "The storage cost should be charged from the deposit."
+    );
+}
+
+/// PrepareNft can't be executed if the Artist did not deposit for the storage
+#[test]
+#[should_panic(expected = "Not enough storage deposit for the draft!")]
+fn prepare_nft_without_storage_deposit_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.draft_storage_withdraw(None);                                                      // Alice takes back the deposit before the draft is created
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Deleting the draft removes it from the indexes, and cancels the schedule
+#[test]
+fn delete_prepared_nft() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    let balance = contract.get_draft_storage_balance(accounts(0));
+
+    id = delete_prepared_nft_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert!(contract.get_draft(0).is_none(), "The draft should be deleted.");
+    assert!(contract.get_drafts_by_artist(accounts(0), 0, 10).is_empty(), "Alice should not have drafts.");
+    assert!(
+        contract.get_drafts_by_contract(AccountId::new_unchecked("minting-contract-1.near".to_string()), 0, 10).is_empty(),
+        "The minting contract should not have drafts."
+    );
+    assert_eq!(balance, contract.get_draft_storage_balance(accounts(0)), "The storage cost is sent back, not added to the balance.");
+}
+
+/// Only the Artist who created the draft can delete it
+#[test]
+#[should_panic(expected = "You can only delete prepared NFTs that you originally created!")]
+fn delete_prepared_nft_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // Bob
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(1), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = delete_prepared_nft_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// The draft can't be deleted while the mint_root call is in progress
+#[test]
+#[should_panic(expected = "The draft is being minted, it can not be deleted!")]
+fn delete_prepared_nft_while_minting_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_artist(&mut context);
+
+    let mut id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    id = delete_prepared_nft_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
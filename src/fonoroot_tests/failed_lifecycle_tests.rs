@@ -0,0 +1,116 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, PromiseError};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    resend_failed_transaction_proposal,
+    write_off_failed_transaction_proposal
+};
+use crate::types::{Action, FailedTransactionStatus};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted, and sending 5 NEAR to Charlie failed (failed transaction 0). Bob (Council) is the caller
+fn setup_contract_with_failed_transaction(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).block_timestamp(1000).build());
+    contract.transfer_callback(Err(PromiseError::Failed), accounts(2), U128(to_yocto("5")), None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).block_timestamp(2000).build());
+    contract
+}
+
+/// A failed transfer creates a Pending record, the failure is the first event of the history
+#[test]
+fn failed_transaction_is_pending() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_failed_transaction(&mut context);
+
+    let failed_transaction = contract.get_failed_transaction(0).unwrap();
+    assert_eq!(FailedTransactionStatus::Pending, failed_transaction.status, "The status should be Pending.");
+    assert_eq!(1, failed_transaction.history.len(), "There should be 1 event.");
+    assert_eq!(Some(accounts(2)), failed_transaction.history[0].receiver, "The transfer to Charlie failed.");
+    assert_eq!(1000, failed_transaction.history[0].timestamp, "The time of the failure should be saved.");
+}
+
+/// The Council resends the transaction and it fails again: the record keeps its ID, and no new record is created
+#[test]
+fn resend_failed_transaction_fails_again() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transaction(&mut context);
+
+    let id = resend_failed_transaction_proposal(&mut context, &mut contract, 0, accounts(3));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(FailedTransactionStatus::Retried, contract.get_failed_transaction(0).unwrap().status, "The record should be kept while the resend is in flight.");
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.resend_failed_transaction_callback(Err(PromiseError::Failed), 0, accounts(3));
+
+    let failed_transaction = contract.get_failed_transaction(0).unwrap();
+    assert_eq!(FailedTransactionStatus::Pending, failed_transaction.status, "The status should be Pending again.");
+    assert_eq!(accounts(2), failed_transaction.beneficiary, "Charlie should stay the beneficiary.");
+    assert_eq!(1, contract.failed_nonce, "No new record should be created.");
+
+    let statuses: Vec<FailedTransactionStatus> = failed_transaction.history.iter().map(|event| event.status).collect();
+    assert_eq!(
+        vec![FailedTransactionStatus::Pending, FailedTransactionStatus::Retried, FailedTransactionStatus::Pending],
+        statuses,
+        "The history should contain every status change."
+    );
+    assert_eq!(Some(accounts(3)), failed_transaction.history[1].receiver, "The resend went to Danny.");
+}
+
+/// The resend is successful
+#[test]
+fn resend_failed_transaction_settles() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transaction(&mut context);
+
+    let id = resend_failed_transaction_proposal(&mut context, &mut contract, 0, accounts(3));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.resend_failed_transaction_callback(Ok(()), 0, accounts(3));
+
+    let failed_transaction = contract.get_failed_transaction(0).unwrap();
+    assert_eq!(FailedTransactionStatus::Settled, failed_transaction.status, "The status should be Settled.");
+    assert_eq!(3, failed_transaction.history.len(), "There should be 3 events.");
+    assert!(contract.get_failed_transactions_for(accounts(2)).is_empty(), "Nothing is owed to Charlie.");
+    assert!(contract.open_failed_transactions.get(&accounts(2)).is_none(), "The index of Charlie should be removed.");
+}
+
+/// The Council writes off the transaction, it can't be retried after that
+#[test]
+#[should_panic(expected = "Only pending failed transactions can be sent again!")]
+fn write_off_failed_transaction_test() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transaction(&mut context);
+
+    let id = write_off_failed_transaction_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let failed_transaction = contract.get_failed_transaction(0).unwrap();
+    assert_eq!(FailedTransactionStatus::WrittenOff, failed_transaction.status, "The status should be WrittenOff.");
+    assert_eq!(None, failed_transaction.history[1].receiver, "Nothing was sent.");
+    assert!(contract.get_failed_transactions_for(accounts(2)).is_empty(), "Nothing is owed to Charlie.");
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).build());
+    contract.retry_failed_transaction(0, None);
+}
+
+/// A transaction that is being resent can't be written off
+#[test]
+#[should_panic(expected = "Only pending failed transactions can be written off!")]
+fn write_off_retried_transaction_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transaction(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).build());
+    contract.retry_failed_transaction(0, None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = write_off_failed_transaction_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
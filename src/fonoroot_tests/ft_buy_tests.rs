@@ -0,0 +1,113 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, PromiseOrValue};
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    add_token_to_whitelist_proposal,
+    set_ft_price_proposal
+};
+use crate::types::{Action};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted, and "usdc.near" is whitelisted
+fn setup_contract_with_song(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    // Whitelist the token
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // This is Bob
+    let id = add_token_to_whitelist_proposal(context, &mut contract, AccountId::new_unchecked("usdc.near".to_string()));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).signer_account_id(accounts(0)).build());     // Alice
+    contract
+}
+
+/// The Artist can set the price in a whitelisted token
+#[test]
+fn set_ft_price_test() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    let id = set_ft_price_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("usdc.near".to_string()), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert_eq!(
+        Some(U128(5_000_000)),
+        contract.get_ft_price(AccountId::new_unchecked("minting-contract-1.near".to_string()), "fono-root-0".to_string(), AccountId::new_unchecked("usdc.near".to_string())),
+        "The price in USDC should be 5 USDC."
+    );
+}
+
+/// The price can't be set in a token that is not whitelisted
+#[test]
+#[should_panic(expected = "This token is not whitelisted!")]
+fn set_ft_price_not_whitelisted_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    let id = set_ft_price_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("scam-token.near".to_string()), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Buying with a token: after success, the balance is updated, and the difference is refunded
+#[test]
+fn ft_buy_nft_updates_balance() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    let id = set_ft_price_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("usdc.near".to_string()), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Charlie sends 6 USDC, through the token contract
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("usdc.near".to_string())).signer_account_id(accounts(2)).build());
+    let msg = "{\"root_id\": \"fono-root-0\", \"minting_contract\": \"minting-contract-1.near\"}".to_string();
+    let result = contract.ft_on_transfer(accounts(2), U128(6_000_000), msg);
+    assert!(matches!(result, PromiseOrValue::Promise(_)), "ft_on_transfer should return a promise.");
+
+    let refund = contract.ft_buy_nft_callback(Ok(true.into()), 0, accounts(2), AccountId::new_unchecked("usdc.near".to_string()), U128(6_000_000), U128(5_000_000), 0);
+    assert_eq!(U128(1_000_000), refund, "1 USDC should be refunded.");
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(Some(&5_000_000), income_table.ft_balances.get(&AccountId::new_unchecked("usdc.near".to_string())), "The USDC balance should be 5 USDC.");
+    assert_eq!(Some(&5_000_000), income_table.ft_total_income.get(&AccountId::new_unchecked("usdc.near".to_string())), "The USDC total income should be 5 USDC.");
+    assert_eq!(0, income_table.current_balance, "The NEAR balance should not change.");
+}
+
+/// If moving the NFT failed, every token is refunded
+#[test]
+fn ft_buy_nft_failed_refunds_everything() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    let refund = contract.ft_buy_nft_callback(Ok(false.into()), 0, accounts(2), AccountId::new_unchecked("usdc.near".to_string()), U128(6_000_000), U128(5_000_000), 0);
+    assert_eq!(U128(6_000_000), refund, "Everything should be refunded.");
+    assert!(contract.income_tables.get(&0).unwrap().ft_balances.is_empty(), "The balance should not change.");
+}
+
+/// Tokens that are not whitelisted are rejected
+#[test]
+#[should_panic(expected = "This token is not whitelisted!")]
+fn ft_buy_nft_not_whitelisted_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("scam-token.near".to_string())).signer_account_id(accounts(2)).build());
+    let msg = "{\"root_id\": \"fono-root-0\", \"minting_contract\": \"minting-contract-1.near\"}".to_string();
+    contract.ft_on_transfer(accounts(2), U128(6_000_000), msg);
+}
+
+/// Not enough tokens were sent
+#[test]
+#[should_panic(expected = "Not enough tokens were sent to buy the NFT")]
+fn ft_buy_nft_not_enough_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_song(&mut context);
+
+    let id = set_ft_price_proposal(&mut context, &mut contract, 0, AccountId::new_unchecked("usdc.near".to_string()), Some(U128(5_000_000)));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("usdc.near".to_string())).signer_account_id(accounts(2)).build());
+    let msg = "{\"root_id\": \"fono-root-0\", \"minting_contract\": \"minting-contract-1.near\"}".to_string();
+    contract.ft_on_transfer(accounts(2), U128(4_000_000), msg);
+}
@@ -1,15 +1,53 @@
 use std::collections::{HashMap};
-use near_sdk::test_utils::{VMContextBuilder};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
 use near_sdk::{AccountId};
-pub use near_sdk::json_types::{Base64VecU8};
+pub use near_sdk::json_types::{Base64VecU8, U64, U128};
 use near_sdk::testing_env;
 use near_sdk_sim::to_yocto;
-use crate::proposals::{ProposalInput, ProposalKind};
+use crate::proposals::{PolicyParameters, ProposalInput, ProposalKind};
 use crate::policy::{RoleKind, RolePermission};
-use crate::types::{NftDataFromFrontEnd, TokenId, SalePriceInYoctoNear};
+use crate::types::{Action, AutoPayout, NftDataFromFrontEnd, NftDataPatch, TokenId, SalePriceInYoctoNear, ScheduleMintParams, MintRootResult, SaleLimits, PriceSchedule, SplitRules};
+use crate::policy::{VersionedPolicy};
 use crate::Contract;
+use crate::Config;
 
 
+/// Creates a contract, where Alice has a minted song (TreeIndex 0, fono-root-0), with a RevenueTable (Alice 90%, Bob 10%), price is 5 NEAR
+pub fn setup_contract_with_minted_song(context: &mut VMContextBuilder) -> Contract {
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare and mint NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).signer_account_id(accounts(0)).build());     // Artist will be Alice
+    id = prepare_nft_full_proposal(context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "fono-root-0".to_string(),
+    }), accounts(0), 0, Some(id));
+
+    let mut unchecked_table = HashMap::default();
+    unchecked_table.insert(accounts(0), 9000);
+    unchecked_table.insert(accounts(1), 1000);
+    id = add_revenue_table_proposal(context, &mut contract, "fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()), unchecked_table, U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    contract
+}
+
 /// Create a Master Group for the minting contract with @name
 pub fn create_master_group_proposal(context: &mut VMContextBuilder, contract: &mut Contract, name: String) -> u64 {
     testing_env!(context.attached_deposit(to_yocto("0")).build());
@@ -24,9 +62,19 @@ pub fn create_master_group_proposal(context: &mut VMContextBuilder, contract: &m
                             "mint_root:*".to_string(),
                             "prepair_nft:*".to_string(),
                             "update_prepaired_nft:*".to_string(),
+                            "delete_prepared_nft:*".to_string(),
                             "create_revenue_table:*".to_string(),
                             "alter_revenue_table:*".to_string(),
-                            "payout_revenue:*".to_string()
+                            "payout_revenue:*".to_string(),
+                            "schedule_mint:*".to_string(),
+                            "set_ft_price:*".to_string(),
+                            "set_royalty:*".to_string(),
+                            "set_sale_limits:*".to_string(),
+                            "set_price_schedule:*".to_string(),
+                            "update_presale_allowlist:*".to_string(),
+                            "set_split_rules:*".to_string(),
+                            "set_payee_group:*".to_string(),
+                            "set_auto_payout:*".to_string()
                         ].into_iter().collect(),
                     vote_policy: HashMap::default(),
                 }
@@ -65,6 +113,7 @@ pub fn remove_member_from_master_proposal(context: &mut VMContextBuilder, contra
 
 /// This will add an InProgressNft to the list that is ready to be minted.
 pub fn prepare_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut Contract) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Prepare NFT".to_string(),
@@ -74,13 +123,13 @@ pub fn prepare_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut
                 title: Some("Test NFT".to_string()),
                 desc: Some("Description about a Test NFT".to_string()),
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: Some("QmeCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVUHpb".to_string()),
-                meta_json_hash: Some(Base64VecU8(vec![10,11,12])),
+                meta_json_hash: Some(Base64VecU8(vec![10; 32])),
             }
         },
     })
@@ -88,6 +137,7 @@ pub fn prepare_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut
 
 /// This will try to add a bad InProgressNft, where there is image, but no hash for image.
 pub fn prepare_nft_image_hash_missing_proposal(context: &mut VMContextBuilder, contract: &mut Contract) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Prepare NFT".to_string(),
@@ -99,11 +149,11 @@ pub fn prepare_nft_image_hash_missing_proposal(context: &mut VMContextBuilder, c
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
                 image_hash: None,
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: Some("QmeCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVUHpb".to_string()),
-                meta_json_hash: Some(Base64VecU8(vec![10,11,12])),
+                meta_json_hash: Some(Base64VecU8(vec![10; 32])),
             }
         },
     })
@@ -111,6 +161,7 @@ pub fn prepare_nft_image_hash_missing_proposal(context: &mut VMContextBuilder, c
 
 /// This will try to add a bad InProgressNft, where there is meta, but no hash for meta.
 pub fn prepare_nft_meta_hash_missing_proposal(context: &mut VMContextBuilder, contract: &mut Contract) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Prepare NFT".to_string(),
@@ -120,11 +171,11 @@ pub fn prepare_nft_meta_hash_missing_proposal(context: &mut VMContextBuilder, co
                 title: Some("Test NFT".to_string()),
                 desc: Some("Description about a Test NFT".to_string()),
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: Some("QmeCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVUHpb".to_string()),
                 meta_json_hash: None,
             }
@@ -134,6 +185,7 @@ pub fn prepare_nft_meta_hash_missing_proposal(context: &mut VMContextBuilder, co
 
 /// This will add an InProgressNft to the list that is not ready to be minted, it is half-ready
 pub fn prepare_nft_half_ready_proposal(context: &mut VMContextBuilder, contract: &mut Contract) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Prepare NFT".to_string(),
@@ -143,11 +195,11 @@ pub fn prepare_nft_half_ready_proposal(context: &mut VMContextBuilder, contract:
                 title: Some("Test NFT".to_string()),
                 desc: None,
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: None,
                 meta_json_hash: None,
             }
@@ -155,8 +207,33 @@ pub fn prepare_nft_half_ready_proposal(context: &mut VMContextBuilder, contract:
     })
 }
 
+/// This will prepare an NFT, where every field is filled, but image_hash is too short, music is not a CID, and animation_url is not a URL
+pub fn prepare_nft_invalid_proposal(context: &mut VMContextBuilder, contract: &mut Contract) -> u64 {
+    deposit_draft_storage(context, contract);
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Prepare NFT".to_string(),
+        kind: ProposalKind::PrepareNft {
+            nft_data: NftDataFromFrontEnd {
+                contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+                title: Some("Test NFT".to_string()),
+                desc: Some("Description of the test NFT".to_string()),
+                image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
+                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                music_folder_cid: Some("not-a-cid".to_string()),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
+                animation_url: Some("song.mp3".to_string()),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
+                meta_json_cid: Some("QmeCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVUHpb".to_string()),
+                meta_json_hash: Some(Base64VecU8(vec![10; 32])),
+            }
+        },
+    })
+}
+
 /// This will update an InProgressNft with the below data. After this, the NFT will be ready to be minted.
 pub fn update_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Update prepared NFT (will be ready after this)".to_string(),
@@ -167,13 +244,38 @@ pub fn update_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut C
                 title: Some("Test NFT".to_string()),
                 desc: Some("Description of the updated NFT".to_string()),
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
+                music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
+                animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
+                meta_json_cid: Some("QmxCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVabcd".to_string()),
+                meta_json_hash: Some(Base64VecU8(vec![15; 32])),
+            }
+        },
+    })
+}
+
+/// This will update an InProgressNft with full data, and move it to another minting contract
+pub fn update_nft_move_to_contract_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64, minting_contract: AccountId) -> u64 {
+    deposit_draft_storage(context, contract);
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Update prepared NFT (move to other minting contract)".to_string(),
+        kind: ProposalKind::UpdatePrepairedNft {
+            id: id,
+            new_nft_data: NftDataFromFrontEnd {
+                contract: minting_contract,
+                title: Some("Test NFT".to_string()),
+                desc: Some("Description of the updated NFT".to_string()),
+                image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: Some("QmxCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVabcd".to_string()),
-                meta_json_hash: Some(Base64VecU8(vec![15,16,17])),
+                meta_json_hash: Some(Base64VecU8(vec![15; 32])),
             }
         },
     })
@@ -181,6 +283,7 @@ pub fn update_nft_full_proposal(context: &mut VMContextBuilder, contract: &mut C
 
 /// This will try to update an InProgressNft, but the data is not good, music_folder_hash is missing.
 pub fn update_nft_music_hash_missing_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Update prepared NFT (will be ready after this)".to_string(),
@@ -191,13 +294,13 @@ pub fn update_nft_music_hash_missing_proposal(context: &mut VMContextBuilder, co
                 title: Some("Test NFT".to_string()),
                 desc: Some("Description of the updated NFT".to_string()),
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
                 music_folder_hash: None,
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: Some("QmxCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVabcd".to_string()),
-                meta_json_hash: Some(Base64VecU8(vec![15,16,17])),
+                meta_json_hash: Some(Base64VecU8(vec![15; 32])),
             }
         },
     })
@@ -205,6 +308,7 @@ pub fn update_nft_music_hash_missing_proposal(context: &mut VMContextBuilder, co
 
 /// This will update an InProgressNft with the below data. After this, the NFT still won't be ready to be minted, some data is missing.
 pub fn update_nft_half_ready_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64) -> u64 {
+    deposit_draft_storage(context, contract);
     testing_env!(context.attached_deposit(to_yocto("0")).build());
     contract.add_proposal(ProposalInput {
         description: "Update prepared NFT (will be ready after this)".to_string(),
@@ -215,11 +319,11 @@ pub fn update_nft_half_ready_proposal(context: &mut VMContextBuilder, contract:
                 title: Some("Test NFT".to_string()),
                 desc: Some("Description of the updated NFT".to_string()),
                 image_cid: Some("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string()),
-                image_hash: Some(Base64VecU8(vec![1,2,3])),
+                image_hash: Some(Base64VecU8(vec![1; 32])),
                 music_folder_cid: Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                music_folder_hash: Some(Base64VecU8(vec![4,5,6])),
+                music_folder_hash: Some(Base64VecU8(vec![4; 32])),
                 animation_url: Some("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()),
-                animation_url_hash: Some(Base64VecU8(vec![7,8,9])),
+                animation_url_hash: Some(Base64VecU8(vec![7; 32])),
                 meta_json_cid: None,
                 meta_json_hash: None,
             }
@@ -227,6 +331,36 @@ pub fn update_nft_half_ready_proposal(context: &mut VMContextBuilder, contract:
     })
 }
 
+/// This will patch the InProgressMetadata, only the fields in `patch` are changed
+pub fn patch_nft_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64, patch: NftDataPatch) -> u64 {
+    deposit_draft_storage(context, contract);
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Patch Prepared NFT".to_string(),
+        kind: ProposalKind::PatchPrepairedNft {
+            id: id,
+            patch: patch
+        },
+    })
+}
+
+/// Deposit 1 NEAR for the storage of the drafts, so PrepareNft and UpdatePrepairedNft can be executed
+pub fn deposit_draft_storage(context: &mut VMContextBuilder, contract: &mut Contract) {
+    testing_env!(context.attached_deposit(to_yocto("1")).build());
+    contract.draft_storage_deposit();
+}
+
+/// This will delete the NFT that was previously prepared by prepare_nft_add_proposal
+pub fn delete_prepared_nft_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Delete Prepared NFT".to_string(),
+        kind: ProposalKind::DeletePreparedNft {
+            id: id
+        },
+    })
+}
+
 /// This will mint the NFT that was previously prepared by prepare_nft_add_proposal
 pub fn mint_root_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64) -> u64 {
     testing_env!(context.attached_deposit(to_yocto("0")).build());
@@ -238,6 +372,20 @@ pub fn mint_root_proposal(context: &mut VMContextBuilder, contract: &mut Contrac
     })
 }
 
+/// This will schedule the prepared NFT to be minted at `timestamp`
+pub fn schedule_mint_proposal(context: &mut VMContextBuilder, contract: &mut Contract, id: u64, timestamp: u64) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Schedule Mint".to_string(),
+        kind: ProposalKind::ScheduleMint {
+            params: ScheduleMintParams {
+                id: id,
+                timestamp: U64(timestamp)
+            }
+        },
+    })
+}
+
 /// This will add a RevenueTable to an already minted NFT
 pub fn add_revenue_table_proposal(context: &mut VMContextBuilder, contract: &mut Contract, root_id: TokenId,  minting_contract: AccountId, unsafe_table: HashMap<AccountId, u64>, price: SalePriceInYoctoNear ) -> u64 {
     testing_env!(context.attached_deposit(to_yocto("0")).build());
@@ -263,4 +411,170 @@ pub fn alter_revenue_table_proposal(context: &mut VMContextBuilder, contract: &m
             price: price
         }
     })
-}
\ No newline at end of file
+}
+
+/// Council adds a NEP-141 token to the whitelist
+pub fn add_token_to_whitelist_proposal(context: &mut VMContextBuilder, contract: &mut Contract, token_id: AccountId) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Add token to whitelist".to_string(),
+        kind: ProposalKind::AddTokenToWhitelist {
+            token_id: token_id
+        }
+    })
+}
+
+/// This will set the price of a song in a NEP-141 token
+pub fn set_ft_price_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, token_id: AccountId, price: Option<U128>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set price in NEP-141 token".to_string(),
+        kind: ProposalKind::SetFtPrice {
+            tree_index: index,
+            token_id: token_id,
+            price: price
+        }
+    })
+}
+/// This will set the royalty of the song for secondary sales
+pub fn set_royalty_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, royalty: u32) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Royalty".to_string(),
+        kind: ProposalKind::SetRoyalty {
+            tree_index: index,
+            royalty: royalty
+        },
+    })
+}
+
+/// This will set the sale window and the editions cap of the song
+pub fn set_sale_limits_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, limits: SaleLimits) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Sale Limits".to_string(),
+        kind: ProposalKind::SetSaleLimits {
+            tree_index: index,
+            limits: limits
+        },
+    })
+}
+
+/// This will set the price schedule of the song
+pub fn set_price_schedule_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, schedule: Option<PriceSchedule>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Price Schedule".to_string(),
+        kind: ProposalKind::SetPriceSchedule {
+            tree_index: index,
+            schedule: schedule
+        },
+    })
+}
+
+/// This will add accounts to, and remove accounts from the presale allowlist of the song
+pub fn update_presale_allowlist_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, add: Vec<AccountId>, remove: Vec<AccountId>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Update Presale Allowlist".to_string(),
+        kind: ProposalKind::UpdatePresaleAllowlist {
+            tree_index: index,
+            add: add,
+            remove: remove
+        },
+    })
+}
+
+/// This will send a failed transaction again, to `new_address`
+pub fn resend_failed_transaction_proposal(context: &mut VMContextBuilder, contract: &mut Contract, failed_id: u64, new_address: AccountId) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Resend Failed Transaction".to_string(),
+        kind: ProposalKind::ResendFailedTransaction {
+            failed_id: failed_id,
+            new_address: new_address
+        },
+    })
+}
+
+/// This will write off a failed transaction
+pub fn write_off_failed_transaction_proposal(context: &mut VMContextBuilder, contract: &mut Contract, failed_id: u64) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Write Off Failed Transaction".to_string(),
+        kind: ProposalKind::WriteOffFailedTransaction {
+            failed_id: failed_id
+        },
+    })
+}
+
+/// This will set the split rules of the song
+pub fn set_split_rules_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, rules: Option<SplitRules>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Split Rules".to_string(),
+        kind: ProposalKind::SetSplitRules {
+            tree_index: index,
+            rules: rules
+        },
+    })
+}
+
+/// This will create or update a payee group
+pub fn set_payee_group_proposal(context: &mut VMContextBuilder, contract: &mut Contract, group_id: AccountId, unsafe_table: HashMap<AccountId, u64>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Payee Group".to_string(),
+        kind: ProposalKind::SetPayeeGroup {
+            group_id: group_id,
+            unsafe_table: unsafe_table
+        },
+    })
+}
+
+/// This will set the automatic payout rule of the song
+pub fn set_auto_payout_proposal(context: &mut VMContextBuilder, contract: &mut Contract, index: u64, auto_payout: Option<AutoPayout>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Set Automatic Payout".to_string(),
+        kind: ProposalKind::SetAutoPayout {
+            tree_index: index,
+            auto_payout: auto_payout
+        },
+    })
+}
+
+/// This will pay out the songs in `tree_index_list`
+pub fn payout_revenue_proposal(context: &mut VMContextBuilder, contract: &mut Contract, tree_index_list: Vec<u64>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Payout Revenue".to_string(),
+        kind: ProposalKind::PayoutRevenue {
+            tree_index_list: tree_index_list
+        },
+    })
+}
+
+/// This will update the parameters of the Policy
+pub fn update_policy_parameters_proposal(context: &mut VMContextBuilder, contract: &mut Contract, parameters: PolicyParameters) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Update Policy Parameters".to_string(),
+        kind: ProposalKind::ChangePolicyUpdateParameters {
+            parameters: parameters
+        },
+    })
+}
+
+/// This will send collected platform fees to `receiver_id`
+pub fn withdraw_platform_fees_proposal(context: &mut VMContextBuilder, contract: &mut Contract, receiver_id: AccountId, amount: U128, token_id: Option<AccountId>) -> u64 {
+    testing_env!(context.attached_deposit(to_yocto("0")).build());
+    contract.add_proposal(ProposalInput {
+        description: "Withdraw Platform Fees".to_string(),
+        kind: ProposalKind::WithdrawPlatformFees {
+            receiver_id: receiver_id,
+            amount: amount,
+            token_id: token_id
+        },
+    })
+}
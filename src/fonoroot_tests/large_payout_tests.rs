@@ -0,0 +1,130 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, Gas};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    alter_revenue_table_proposal,
+    payout_revenue_proposal,
+    update_policy_parameters_proposal
+};
+use crate::proposals::{PolicyParameters};
+use crate::types::{Action, PayoutOutcome};
+use crate::Contract;
+
+
+/// RevenueTable with `length` beneficiaries (payee-0.near, payee-1.near ...), payee-0.near gets the rounding leftover
+fn big_table(length: u64) -> HashMap<AccountId, u64> {
+    let mut table = HashMap::new();
+    for i in 0..length {
+        let share = if i == 0 { 10_000 - (length - 1) * (10_000 / length) } else { 10_000 / length };
+        table.insert(AccountId::new_unchecked(format!("payee-{}.near", i)), share);
+    }
+    table
+}
+
+/// Bob (Council) updates the parameters of the Policy, then Alice continues
+fn update_parameters(context: &mut VMContextBuilder, contract: &mut Contract, max_revenue_table_length: Option<u32>, max_payout_transfers: Option<u32>) {
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = update_policy_parameters_proposal(context, contract, PolicyParameters {
+        proposal_bond: None,
+        proposal_period: None,
+        bounty_bond: None,
+        bounty_forgiveness_period: None,
+        max_revenue_table_length: max_revenue_table_length,
+        max_payout_transfers: max_payout_transfers,
+        platform_fee: None,
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+    testing_env!(context.predecessor_account_id(accounts(0)).signer_account_id(accounts(0)).build());
+}
+
+/// Song 0 of Alice has a RevenueTable with `length` beneficiaries, and 15 NEAR is waiting for PayoutRevenue
+fn setup_contract_with_big_table(context: &mut VMContextBuilder, length: u64) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+    let id = alter_revenue_table_proposal(context, &mut contract, 0, big_table(length), U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.current_balance = to_yocto("15");
+    contract.income_tables.insert(&0, &income_table);
+    contract
+}
+
+/// A RevenueTable with the maximum length is paid out over several calls, `max_payout_transfers` at a time
+#[test]
+fn full_table_paid_out_in_steps() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_big_table(&mut context, 15);
+    update_parameters(&mut context, &mut contract, None, Some(6));
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert_eq!(vec![(0, PayoutOutcome::Paid { amount: U128(to_yocto("15")), ft_amounts: vec![] })], progress.results, "The whole balance should be paid out.");
+    assert_eq!(0, contract.income_tables.get(&0).unwrap().current_balance, "The balance should be nulled.");
+    let queue = contract.get_payout_queue(0, 20);
+    assert_eq!(9, queue.len(), "6 transfers should be sent, 9 should wait in the queue.");
+    assert_eq!(
+        queue.iter().map(|(_, transfer)| transfer.amount.0).sum::<u128>(),
+        contract.queued_revenue,
+        "The queued $NEAR should be tracked."
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(3)).signer_account_id(accounts(3)).build());   // Danny, anyone can call this
+    assert_eq!(6, contract.process_payout_queue(), "The next 6 transfers should be sent.");
+    assert_eq!(3, contract.process_payout_queue(), "The last 3 transfers should be sent.");
+    assert!(contract.get_payout_queue(0, 20).is_empty(), "The queue should be empty.");
+    assert_eq!(0, contract.queued_revenue, "Nothing should be queued.");
+}
+
+/// The transfers stop when the gas runs out, the rest stays in the queue
+#[test]
+fn payout_queue_is_limited_by_gas() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_big_table(&mut context, 15);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    testing_env!(context.prepaid_gas(Gas(50_000_000_000_000)).build());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let queued = contract.get_payout_queue(0, 20).len();
+    assert!(queued > 0 && queued < 15, "Some of the transfers should be sent, the others should wait in the queue.");
+
+    testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+    assert_eq!(queued as u64, contract.process_payout_queue(), "The rest should be sent with enough gas.");
+}
+
+/// The maximum length of the RevenueTable comes from the Policy
+#[test]
+fn revenue_table_length_from_policy() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    update_parameters(&mut context, &mut contract, Some(20), None);
+
+    let id = alter_revenue_table_proposal(&mut context, &mut contract, 0, big_table(20), U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(20, contract.get_catalogue(accounts(0))[0].1.as_ref().unwrap().revenue_table.len(), "The table with 20 beneficiaries should be saved.");
+}
+
+/// With the default Policy, a RevenueTable can't have more than 15 beneficiaries
+#[test]
+#[should_panic]
+fn revenue_table_too_long_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = alter_revenue_table_proposal(&mut context, &mut contract, 0, big_table(16), U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// The queue can't be processed when it is empty
+#[test]
+#[should_panic(expected = "The payout queue is empty!")]
+fn process_empty_payout_queue_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    contract.process_payout_queue();
+}
@@ -46,9 +46,19 @@ fn master_group_parameters_are_correct() {
         "mint_root:*".to_string(),
         "prepair_nft:*".to_string(),
         "update_prepaired_nft:*".to_string(),
+        "delete_prepared_nft:*".to_string(),
         "create_revenue_table:*".to_string(),
         "alter_revenue_table:*".to_string(),
-        "payout_revenue:*".to_string()
+        "payout_revenue:*".to_string(),
+        "schedule_mint:*".to_string(),
+        "set_ft_price:*".to_string(),
+        "set_royalty:*".to_string(),
+        "set_sale_limits:*".to_string(),
+        "set_price_schedule:*".to_string(),
+        "update_presale_allowlist:*".to_string(),
+        "set_split_rules:*".to_string(),
+        "set_payee_group:*".to_string(),
+        "set_auto_payout:*".to_string()
     ].into_iter().collect();
 
     let zero_member: RoleKind = RoleKind::Group(vec![].into_iter().collect());
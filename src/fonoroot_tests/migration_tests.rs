@@ -0,0 +1,253 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, TreeMap};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{env, testing_env, AccountId};
+use crate::migration::{OldContract, OldPolicy, OldVersionedPolicy, OldPolicyParameters, OldProposal, OldProposalKind, OldVersionedProposal, OldIncomeTable, OldFailedTransaction, OldCatalogue, OldCatalogueEntry, OldInProgressMetadata, MigrationProgress, STATE_VERSION};
+use crate::policy::{default_policy, VersionedPolicy};
+use crate::proposals::{ProposalKind, ProposalStatus};
+use crate::types::{Action, FailedTransactionStatus, RevenueTable, UniqId};
+use crate::{Contract, Config, StorageKeys};
+
+
+/// Writes a version 1 state with 2 songs of Alice, 1 draft of Alice, 1 failed transaction,
+/// and 3 proposals (UpdateParameters, the old ScheduleMint placeholder, PayoutRevenue)
+fn write_v1_state() {
+    let mut income_tables: TreeMap<u64, OldIncomeTable> = TreeMap::new(StorageKeys::IncomeTables);
+    let mut catalogue: OldCatalogue = UnorderedMap::new(StorageKeys::ArtistCatalogue(0));
+    let mut uniq_id_to_tree_index = UnorderedMap::new(StorageKeys::UniqueIdToTreeIndex);
+    for index in 0..2 {
+        income_tables.insert(&index, &OldIncomeTable {
+            total_income: 100,
+            current_balance: 10,
+            root_id: format!("fono-root-{}", index),
+            contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+            owner: accounts(0),
+            price: Some(U128(100)),
+        });
+        uniq_id_to_tree_index.insert(&UniqId::new(AccountId::new_unchecked("minting-contract-1.near".to_string()), format!("fono-root-{}", index)), &index);
+    }
+    let mut table = HashMap::new();
+    table.insert(accounts(0), 10000);
+    catalogue.insert(&0, &Some(OldCatalogueEntry { revenue_table: RevenueTable::new(table, 15).unwrap() }));
+    catalogue.insert(&1, &None);
+    let mut catalogues = LookupMap::new(StorageKeys::Catalogues);
+    catalogues.insert(&accounts(0), &catalogue);
+
+    let mut failed_transactions = UnorderedMap::new(StorageKeys::FailedTransactions);
+    failed_transactions.insert(&0, &OldFailedTransaction { beneficiary: accounts(2), amount: 7 });
+
+    let mut in_progress_nfts = LookupMap::new(StorageKeys::InProgressNfts);
+    in_progress_nfts.insert(&0, &OldInProgressMetadata {
+        id: 0,
+        initiated: 0,
+        artist: accounts(0),
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        scheduled: None,
+        title: Some("Test NFT".to_string()),
+        desc: None,
+        meta: None,
+        meta_hash: None,
+        image: None,
+        image_hash: None,
+        music: None,
+        music_hash: None,
+        animation_url: None,
+        animation_url_hash: None,
+    });
+
+    let mut proposals = LookupMap::new(StorageKeys::Proposals);
+    let kinds = vec![
+        OldProposalKind::ChangePolicyUpdateParameters { parameters: OldPolicyParameters {
+            proposal_bond: Some(U128(5)),
+            proposal_period: None,
+            bounty_bond: None,
+            bounty_forgiveness_period: None,
+        }},
+        OldProposalKind::ScheduleMint { params: "placeholder".to_string() },
+        OldProposalKind::PayoutRevenue { tree_index_list: vec![0, 1] },
+    ];
+    for (id, kind) in kinds.into_iter().enumerate() {
+        proposals.insert(&(id as u64), &OldVersionedProposal::Default(OldProposal {
+            proposer: accounts(0),
+            description: format!("Proposal {}", id),
+            kind: kind,
+            status: ProposalStatus::InProgress,
+            vote_counts: HashMap::new(),
+            votes: HashMap::new(),
+            submission_time: U64(0),
+        }));
+    }
+
+    let policy = default_policy(vec![accounts(1).into()]);
+    let old_policy = OldVersionedPolicy::Current(OldPolicy {
+        roles: policy.roles,
+        default_vote_policy: policy.default_vote_policy,
+        proposal_bond: policy.proposal_bond,
+        proposal_period: policy.proposal_period,
+        bounty_bond: policy.bounty_bond,
+        bounty_forgiveness_period: policy.bounty_forgiveness_period,
+    });
+
+    let old = OldContract {
+        config: LazyOption::new(StorageKeys::Config, Some(&Config::test_config())),
+        policy: LazyOption::new(StorageKeys::Policy, Some(&old_policy)),
+        locked_amount: 0,
+        staking_id: None,
+        total_delegation_amount: 0,
+        delegations: LookupMap::new(StorageKeys::Delegations),
+        last_proposal_id: 3,
+        proposals: proposals,
+        last_bounty_id: 0,
+        bounties: LookupMap::new(StorageKeys::Bounties),
+        bounty_claimers: LookupMap::new(StorageKeys::BountyClaimers),
+        bounty_claims_count: LookupMap::new(StorageKeys::BountyClaimCounts),
+        blobs: LookupMap::new(StorageKeys::Blobs),
+        in_progress_nfts: in_progress_nfts,
+        in_progress_nonce: 1,
+        catalogues: catalogues,
+        income_tables: income_tables,
+        uniq_id_to_tree_index: uniq_id_to_tree_index,
+        tree_index: 2,
+        failed_transactions: failed_transactions,
+        failed_nonce: 1,
+    };
+    env::state_write(&old);
+}
+
+/// migrate() converts the Contract struct, and starts the migration of the collections
+#[test]
+fn migrate_from_v1_starts_migration() {
+    let context = VMContextBuilder::new();
+    testing_env!(context.build());
+    write_v1_state();
+
+    let contract = Contract::migrate();
+    assert_eq!(STATE_VERSION, contract.get_state_version(), "The state should have the current version.");
+    assert_eq!(
+        Some(MigrationProgress { from_version: 1, next_tree_index: 0, next_failed_nonce: 0, next_draft_id: 0, next_proposal_id: 0 }),
+        contract.get_migration_progress(),
+        "The migration of the collections should be in progress."
+    );
+    assert_eq!(2, contract.get_number_of_nfts(), "The TreeIndex should be kept.");
+
+    let policy = contract.get_policy();
+    assert_eq!(1, policy.roles.len(), "The roles should be kept.");
+    assert_eq!(15, policy.max_revenue_table_length, "The RevenueTable limit should be the one of version 1.");
+}
+
+/// migrate_state() can be called multiple times, and converts every element
+#[test]
+fn migrate_state_in_batches() {
+    let context = VMContextBuilder::new();
+    testing_env!(context.build());
+    write_v1_state();
+    let mut contract = Contract::migrate();
+
+    let progress = contract.migrate_state(1);
+    assert_eq!(
+        Some(MigrationProgress { from_version: 1, next_tree_index: 1, next_failed_nonce: 0, next_draft_id: 0, next_proposal_id: 0 }),
+        progress,
+        "Only the first song should be converted."
+    );
+    assert_eq!(None, contract.migrate_state(10), "The migration should be finished.");
+
+    for index in 0..2 {
+        let income_table = contract.get_single_income_table(index);
+        assert_eq!(100, income_table.total_income, "The total income should be kept.");
+        assert_eq!(10, income_table.current_balance, "The balance should be kept.");
+        assert!(income_table.ft_balances.is_empty(), "New fields should be empty.");
+    }
+
+    let catalogue = contract.get_catalogue(accounts(0));
+    assert_eq!(2, catalogue.len(), "Both songs should be in the Catalogue.");
+    assert_eq!(Some(&10000), catalogue[0].1.as_ref().unwrap().revenue_table.get(&accounts(0)), "The RevenueTable should be kept.");
+    assert!(catalogue[0].1.as_ref().unwrap().split_rules.is_none(), "Old songs don't have split rules.");
+
+    let failed_transactions = contract.get_failed_transactions(0, 10);
+    assert_eq!(7, failed_transactions[0].1.amount, "The amount should be kept.");
+    assert_eq!(None, failed_transactions[0].1.token_id, "Old failed transactions were $NEAR transactions.");
+    assert_eq!(FailedTransactionStatus::Pending, failed_transactions[0].1.status, "Old failed transactions are still owed.");
+    assert_eq!(1, contract.get_failed_transactions_for(accounts(2)).len(), "The failed transaction should be indexed for Charlie.");
+
+    assert_eq!(1, contract.get_drafts_by_artist(accounts(0), 0, 10).len(), "The draft should be indexed for Alice.");
+
+    match contract.get_proposal(0).proposal.kind {
+        ProposalKind::ChangePolicyUpdateParameters { parameters } => {
+            assert_eq!(Some(U128(5)), parameters.proposal_bond, "The parameters should be kept.");
+            assert_eq!(None, parameters.platform_fee, "New parameters should not be set.");
+        },
+        _ => panic!("The kind of proposal 0 should be kept."),
+    }
+    assert!(matches!(contract.get_proposal(1).proposal.kind, ProposalKind::Vote), "The old ScheduleMint placeholder should be a signaling vote.");
+    let payout = contract.get_proposal(2);
+    assert!(matches!(payout.proposal.kind, ProposalKind::PayoutRevenue { .. }), "The kind of proposal 2 should be kept.");
+    assert_eq!("Proposal 2", payout.proposal.description, "The description should be kept.");
+    assert_eq!(3, contract.get_proposals(0, 10).len(), "Every proposal should be readable.");
+}
+
+/// Proposals can't be acted on, while the migration is in progress
+#[test]
+#[should_panic(expected = "The state migration is not finished yet, call migrate_state()!")]
+fn act_proposal_during_migration_error() {
+    let context = VMContextBuilder::new();
+    testing_env!(context.build());
+    write_v1_state();
+    let mut contract = Contract::migrate();
+
+    contract.act_proposal(2, Action::VoteApprove, None);
+}
+
+/// Songs can't be bought, while the migration is in progress
+#[test]
+#[should_panic(expected = "The state migration is not finished yet, call migrate_state()!")]
+fn buy_nft_during_migration_error() {
+    let context = VMContextBuilder::new();
+    testing_env!(context.build());
+    write_v1_state();
+    let mut contract = Contract::migrate();
+
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+}
+
+/// The views that read songs, drafts, failed transactions or proposals can't be used, while the migration is in progress
+#[test]
+#[should_panic(expected = "The state migration is not finished yet, call migrate_state()!")]
+fn get_proposal_during_migration_error() {
+    let context = VMContextBuilder::new();
+    testing_env!(context.build());
+    write_v1_state();
+    let contract = Contract::migrate();
+
+    contract.get_proposal(0);
+}
+
+/// migrate() on the current version does not change anything
+#[test]
+fn migrate_current_version_is_noop() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),
+    );
+    env::state_write(&contract);
+
+    let migrated = Contract::migrate();
+    assert_eq!(STATE_VERSION, migrated.get_state_version(), "The version should not change.");
+    assert_eq!(None, migrated.get_migration_progress(), "There should be no migration in progress.");
+}
+
+/// migrate_state() can't be called, if there is nothing to migrate
+#[test]
+#[should_panic(expected = "There is no migration in progress!")]
+fn migrate_state_without_migration_error() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),
+    );
+
+    contract.migrate_state(10);
+}
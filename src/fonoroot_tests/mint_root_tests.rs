@@ -1,4 +1,4 @@
-use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
 pub use near_sdk::json_types::{Base64VecU8, U128};
 use near_sdk::{log, testing_env, AccountId};
 use crate::fonoroot_tests::helpers::{
@@ -11,11 +11,12 @@ use crate::fonoroot_tests::helpers::{
 };
 use crate::policy::{VersionedPolicy};
 use crate::types::{Action, MintRootResult, UniqId};
+use crate::proposals::ProposalStatus;
 use crate::Contract;
 use crate::Config;
 
 
-/// Possible to mint NFT, InProgressNft will be removed
+/// Possible to mint NFT, InProgressNft will be in minting state, and it will be removed when the NFT was created
 #[test]
 fn mint_root_removes_prepared_nft() {
     let mut context = VMContextBuilder::new();
@@ -47,6 +48,12 @@ fn mint_root_removes_prepared_nft() {
     id = mint_root_proposal(&mut context, &mut contract, 0);
     contract.act_proposal(id, Action::VoteApprove, None);
 
+    assert!(contract.in_progress_nfts.get(&0).unwrap().minting, "The entry should be in minting state.");
+
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "fono-root-0".to_string(),
+    }), accounts(0), 0, Some(id));
     assert!(contract.in_progress_nfts.get(&0).is_none(), "The entry should have been removed at this point.");
 }
 
@@ -123,9 +130,8 @@ fn mint_root_not_member_error() {
     contract.act_proposal(id, Action::VoteApprove, None);
 }
 
-/// This should panic, because the promise result is Error
+/// If the promise result is Error, the draft is restored, and the proposal is Failed, with a reason
 #[test]
-#[should_panic(expected = "MintRoot promise come back with an error.")]
 fn mint_root_callback_promise_returned_error() {
     let mut context = VMContextBuilder::new();
     testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
@@ -155,7 +161,96 @@ fn mint_root_callback_promise_returned_error() {
     id = mint_root_proposal(&mut context, &mut contract, 0);
     contract.act_proposal(id, Action::VoteApprove, None);
 
-    contract.mint_root_callback(Err(near_sdk::PromiseError::Failed), accounts(0));
+    contract.mint_root_callback(Err(near_sdk::PromiseError::Failed), accounts(0), 0, Some(id));
+
+    let draft = contract.in_progress_nfts.get(&0).expect("The draft should be restored.");
+    assert!(!draft.minting, "The draft should not be in minting state.");
+    assert_eq!(0, contract.tree_index, "No IncomeTable should be created.");
+
+    let proposal = contract.get_proposal(id);
+    assert_eq!(ProposalStatus::Failed, proposal.proposal.status, "The proposal should be Failed.");
+    assert!(proposal.failure_reason.is_some(), "The reason of the failure should be recorded.");
+}
+
+/// After a failed mint, the Artist can retry by finalizing the failed proposal
+#[test]
+fn mint_root_retry_after_failure() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Update the Prepared NFT
+    id = update_nft_full_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Mint the prepared NFT, it fails
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract.mint_root_callback(Err(near_sdk::PromiseError::Failed), accounts(0), 0, Some(id));
+
+    // Retry, in a new transaction
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.act_proposal(id, Action::Finalize, None);
+    assert!(contract.in_progress_nfts.get(&0).unwrap().minting, "The draft should be in minting state again.");
+
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "fono-root-0".to_string(),
+    }), accounts(0), 0, Some(id));
+
+    let proposal = contract.get_proposal(id);
+    assert_eq!(ProposalStatus::Approved, proposal.proposal.status, "The proposal should be Approved.");
+    assert!(proposal.failure_reason.is_none(), "The failure reason should be removed.");
+    assert!(contract.in_progress_nfts.get(&0).is_none(), "The draft should be removed.");
+}
+
+/// A draft that is being minted can not be updated
+#[test]
+#[should_panic(expected = "The draft is being minted, it can not be updated!")]
+fn update_draft_while_minting_error() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = update_nft_full_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Mint the prepared NFT, and try to update it, before the callback
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = update_nft_full_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
 }
 
 /// Test if the TreeIndex is correct, and that the expected UniqId was generated
@@ -194,7 +289,7 @@ fn mint_root_callback_tree_index() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
 
     let uniq_id = UniqId::new(AccountId::new_unchecked("minting-contract-1.near".to_string()), "fono-root-0".to_string());
     assert_eq!(0, contract.uniq_id_to_tree_index.get(&uniq_id).unwrap(), "The TreeIndex for the newly inserted NFT should be 0");
@@ -202,9 +297,8 @@ fn mint_root_callback_tree_index() {
 }
 
 
-/// This should panic, "Duplicate TreeIndex error"
+/// The callback does not panic on "Duplicate TreeIndex error", the new song is not saved
 #[test]
-#[should_panic(expected = "Duplicate TreeIndex error!")]
 fn mint_root_callback_duplicate_tree_index_error() {
     let mut context = VMContextBuilder::new();
     testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
@@ -244,14 +338,24 @@ fn mint_root_callback_duplicate_tree_index_error() {
         root_id: "fono-root-1".to_string(),
     };
 
-    contract.mint_root_callback(Ok(first_result), accounts(0));
+    contract.mint_root_callback(Ok(first_result), accounts(0), 0, Some(id));
     contract.tree_index = 0;
-    contract.mint_root_callback(Ok(second_result), accounts(0));
+    contract.mint_root_callback(Ok(second_result), accounts(0), 0, Some(id));
+
+    assert!(
+        get_logs().iter().any(|log| log.contains("Duplicate TreeIndex error!")),
+        "The error should be logged."
+    );
+    assert_eq!(0, contract.tree_index, "The TreeIndex should not change.");
+    assert_eq!(
+        "fono-root-0".to_string(),
+        contract.income_tables.get(&0).unwrap().root_id,
+        "The IncomeTable of the first song should not be overwritten."
+    );
 }
 
-/// This should fail, because the UniqId already exists
+/// If the UniqId already exists, the callback does not panic: the draft is restored, and the proposal is Failed, so the draft is not stuck in minting state
 #[test]
-#[should_panic(expected = "The UniqId already exists!")]
 fn mint_root_callback_uniq_id_error() {
     let mut context = VMContextBuilder::new();
     testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
@@ -286,8 +390,54 @@ fn mint_root_callback_uniq_id_error() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(result.clone()), accounts(0));
-    contract.mint_root_callback(Ok(result), accounts(0));
+    contract.mint_root_callback(Ok(result.clone()), accounts(0), 0, Some(id));
+
+    // Prepare and mint a second NFT, the minting contract returns the same RootID
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = update_nft_full_proposal(&mut context, &mut contract, 1);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(&mut context, &mut contract, 1);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract.mint_root_callback(Ok(result), accounts(0), 1, Some(id));
+
+    let draft = contract.in_progress_nfts.get(&1).expect("The draft should be restored.");
+    assert!(!draft.minting, "The draft should not be in minting state, it can be updated, deleted or minted again.");
+    assert_eq!(1, contract.tree_index, "No IncomeTable should be created.");
+    let proposal = contract.get_proposal(id);
+    assert_eq!(ProposalStatus::Failed, proposal.proposal.status, "The proposal should be Failed.");
+    assert_eq!(Some("The UniqId already exists!".to_string()), proposal.failure_reason, "The reason of the failure should be recorded.");
+}
+
+/// An invalid RootID does not make the callback panic
+#[test]
+fn mint_root_callback_invalid_root_id() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = update_nft_full_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    contract.mint_root_callback(Ok(MintRootResult {
+        contract: AccountId::new_unchecked("minting-contract-1.near".to_string()),
+        root_id: "not-a-root".to_string(),
+    }), accounts(0), 0, Some(id));
+
+    assert!(!contract.in_progress_nfts.get(&0).unwrap().minting, "The draft should be restored.");
+    assert_eq!(Some("RootID is not valid!".to_string()), contract.get_proposal(id).failure_reason, "The reason of the failure should be recorded.");
 }
 
 /// IncomeTable test
@@ -326,7 +476,7 @@ fn mint_root_callback_income_table_test() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(example_result), accounts(0));
+    contract.mint_root_callback(Ok(example_result), accounts(0), 0, Some(id));
     let inserted_income_table = contract.income_tables.get(&0).unwrap();
     
     assert_eq!(0, inserted_income_table.total_income, "The total_income should be 0");
@@ -373,7 +523,7 @@ fn mint_root_callback_catalogue_test() {
         root_id: "fono-root-0".to_string(),
     };
 
-    contract.mint_root_callback(Ok(first_result), accounts(0));
+    contract.mint_root_callback(Ok(first_result), accounts(0), 0, Some(id));
     let alice_catalogue = contract.catalogues.get(&accounts(0)).unwrap();                       // Get the Catalogue for Alice
 
     assert_eq!(1, alice_catalogue.len(), "The Catalogue for Alice should have exactly 1 entry");
@@ -398,7 +548,7 @@ fn mint_root_callback_catalogue_test() {
         root_id: "fono-root-1".to_string(),
     };
 
-    contract.mint_root_callback(Ok(second_result), accounts(0));
+    contract.mint_root_callback(Ok(second_result), accounts(0), 1, Some(id));
     let alice_catalogue = contract.catalogues.get(&accounts(0)).unwrap();                       // Get the Catalogue for Alice
 
     assert_eq!(2, alice_catalogue.len(), "The Catalogue for Alice should have exactly 2 entry");
@@ -16,5 +16,71 @@ mod create_revenue_tests;
 #[cfg(test)]
 mod alter_revenue_tests;
 
+#[cfg(test)]
+mod schedule_mint_tests;
+
+#[cfg(test)]
+mod ft_buy_tests;
+
+#[cfg(test)]
+mod claim_revenue_tests;
+
+#[cfg(test)]
+mod migration_tests;
+
+#[cfg(test)]
+mod drafts_tests;
+
+#[cfg(test)]
+mod patch_data_tests;
+
+#[cfg(test)]
+mod royalty_tests;
+
+#[cfg(test)]
+mod sale_limits_tests;
+
+#[cfg(test)]
+mod price_schedule_tests;
+
+#[cfg(test)]
+mod presale_tests;
+
+#[cfg(test)]
+mod buy_storage_tests;
+
+#[cfg(test)]
+mod sales_log_tests;
+
+#[cfg(test)]
+mod buy_refund_tests;
+
+#[cfg(test)]
+mod retry_failed_tests;
+
+#[cfg(test)]
+mod failed_lifecycle_tests;
+
+#[cfg(test)]
+mod split_rules_tests;
+
+#[cfg(test)]
+mod payee_groups_tests;
+
+#[cfg(test)]
+mod auto_payout_tests;
+
+#[cfg(test)]
+mod payout_revenue_tests;
+
+#[cfg(test)]
+mod large_payout_tests;
+
+#[cfg(test)]
+mod remainder_tests;
+
+#[cfg(test)]
+mod platform_fee_tests;
+
 #[cfg(test)]
 mod helpers;
\ No newline at end of file
@@ -0,0 +1,131 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{Base64VecU8};
+use near_sdk::{testing_env, AccountId};
+use crate::fonoroot_tests::helpers::{
+    create_master_group_proposal,
+    add_member_to_master_proposal,
+    prepare_nft_full_proposal,
+    patch_nft_proposal
+};
+use crate::policy::{VersionedPolicy};
+use crate::types::{Action, NftDataPatch};
+use crate::Contract;
+use crate::Config;
+
+
+/// Creates a contract, where Alice has a full draft (ID 0) for minting-contract-1.near
+fn setup_contract_with_draft(context: &mut VMContextBuilder) -> Contract {
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    let mut id = create_master_group_proposal(context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = add_member_to_master_proposal(context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_full_proposal(context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// Patching the title keeps every other field
+#[test]
+fn patch_title_keeps_other_fields() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_draft(&mut context);
+    let old_data = contract.in_progress_nfts.get(&0).unwrap();
+
+    let id = patch_nft_proposal(&mut context, &mut contract, 0, NftDataPatch {
+        title: Some(Some("New title".to_string())),
+        ..Default::default()
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let new_data = contract.in_progress_nfts.get(&0).unwrap();
+    assert_eq!(Some("New title".to_string()), new_data.title, "The title should be updated.");
+    assert_eq!(old_data.desc, new_data.desc, "The description should not change.");
+    assert_eq!(old_data.image, new_data.image, "The image CID should not change.");
+    assert_eq!(old_data.music, new_data.music, "The music folder CID should not change.");
+    assert_eq!(old_data.meta, new_data.meta, "The meta CID should not change.");
+    assert_eq!(old_data.contract, new_data.contract, "The minting contract should not change.");
+    assert!(new_data.is_ready(), "The draft should still be ready.");
+}
+
+/// Fields can be cleared with an explicit null
+#[test]
+fn patch_clears_fields() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_draft(&mut context);
+
+    let id = patch_nft_proposal(&mut context, &mut contract, 0, NftDataPatch {
+        animation_url: Some(None),
+        animation_url_hash: Some(None),
+        ..Default::default()
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let new_data = contract.in_progress_nfts.get(&0).unwrap();
+    assert!(new_data.animation_url.is_none(), "The animation URL should be cleared.");
+    assert!(new_data.animation_url_hash.is_none(), "The animation URL hash should be cleared.");
+    assert!(new_data.image.is_some(), "The image CID should not change.");
+}
+
+/// A new CID can be sent without the hash, if the draft already has a hash for it
+#[test]
+fn patch_cid_uses_existing_hash() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_draft(&mut context);
+
+    let id = patch_nft_proposal(&mut context, &mut contract, 0, NftDataPatch {
+        image_cid: Some(Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string())),
+        ..Default::default()
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let new_data = contract.in_progress_nfts.get(&0).unwrap();
+    assert_eq!(Some("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string()), new_data.image, "The image CID should be updated.");
+    assert_eq!(Some(Base64VecU8(vec![1; 32])), new_data.image_hash, "The image hash should not change.");
+}
+
+/// The hash can't be cleared while the CID is still there
+#[test]
+#[should_panic(expected = "Hash has to exist, if image exists!")]
+fn patch_clear_hash_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_draft(&mut context);
+
+    let id = patch_nft_proposal(&mut context, &mut contract, 0, NftDataPatch {
+        image_hash: Some(None),
+        ..Default::default()
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// The draft can only be moved to a minting contract where the Artist is member of the master group
+#[test]
+#[should_panic(expected = "The role was not found.")]
+fn patch_contract_not_member_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_draft(&mut context);
+
+    let id = patch_nft_proposal(&mut context, &mut contract, 0, NftDataPatch {
+        contract: Some(AccountId::new_unchecked("minting-contract-2.near".to_string())),
+        ..Default::default()
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Absent fields and null fields are different in the JSON input
+#[test]
+fn patch_json_absent_and_null() {
+    let patch: NftDataPatch = near_sdk::serde_json::from_str("{ \"title\": \"New title\", \"desc\": null }").unwrap();
+
+    assert_eq!(Some(Some("New title".to_string())), patch.title, "The title should be set.");
+    assert_eq!(Some(None), patch.desc, "The description should be cleared.");
+    assert_eq!(None, patch.image_cid, "The image CID should be kept.");
+    assert_eq!(None, patch.contract, "The minting contract should be kept.");
+}
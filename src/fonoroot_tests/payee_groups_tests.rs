@@ -0,0 +1,199 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{env, testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_payee_group_proposal,
+    set_royalty_proposal,
+    alter_revenue_table_proposal,
+    payout_revenue_proposal
+};
+use crate::types::{Action, PayeeGroup, PayoutOutcome, RevenueTable};
+use crate::Contract;
+
+
+/// ID of a payee group, inside the namespace of the DAO
+fn group(name: &str) -> AccountId {
+    AccountId::new_unchecked(format!("{}.{}", name, env::current_account_id()))
+}
+
+/// Creates (or updates) payee group `group_id`, as the current signer
+fn set_group(context: &mut VMContextBuilder, contract: &mut Contract, group_id: AccountId, members: Vec<(AccountId, u64)>) {
+    let id = set_payee_group_proposal(context, contract, group_id, members.into_iter().collect());
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Song 0 of Alice is minted (price is 5 NEAR), the band (Charlie 50%, Danny 50%) gets 50% of it, Alice gets 50%
+fn setup_contract_with_band(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+    set_group(context, &mut contract, group("band"), vec![(accounts(2), 5000), (accounts(3), 5000)]);
+
+    let mut table = HashMap::new();
+    table.insert(accounts(0), 5000);
+    table.insert(group("band"), 5000);
+    let id = alter_revenue_table_proposal(context, &mut contract, 0, table, U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// The part of the group is split between the members
+#[test]
+fn payee_group_is_split() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert_eq!(U128(to_yocto("2.5")), contract.get_claimable(accounts(0)), "Alice should get 50%.");
+    assert_eq!(U128(to_yocto("1.25")), contract.get_claimable(accounts(2)), "Charlie should get 50% of the part of the band.");
+    assert_eq!(U128(to_yocto("1.25")), contract.get_claimable(accounts(3)), "Danny should get 50% of the part of the band.");
+    assert_eq!(U128(0), contract.get_claimable(group("band")), "Nothing should be credited to the group itself.");
+}
+
+/// When the lineup changes, only the group is updated, the RevenueTable of the song stays the same
+#[test]
+fn update_payee_group() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    set_group(&mut context, &mut contract, group("band"), vec![(accounts(2), 5000), (accounts(4), 5000)]);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert_eq!(U128(0), contract.get_claimable(accounts(3)), "Danny left the band.");
+    assert_eq!(U128(to_yocto("1.25")), contract.get_claimable(accounts(4)), "Eugene joined the band.");
+}
+
+/// Groups can contain other groups, members that are in more groups get the sum
+#[test]
+fn nested_payee_groups() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    set_group(&mut context, &mut contract, group("horns"), vec![(accounts(3), 5000), (accounts(4), 5000)]);
+    set_group(&mut context, &mut contract, group("band"), vec![(accounts(2), 5000), (group("horns"), 5000)]);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert_eq!(U128(to_yocto("1.25")), contract.get_claimable(accounts(2)), "Charlie should get 50% of the band.");
+    assert_eq!(U128(to_yocto("0.625")), contract.get_claimable(accounts(3)), "Danny should get 50% of the horns.");
+    assert_eq!(U128(to_yocto("0.625")), contract.get_claimable(accounts(4)), "Eugene should get 50% of the horns.");
+}
+
+/// Royalties for marketplaces are split between the members too
+#[test]
+fn nft_payout_with_payee_group() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 1000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let payout = contract.nft_payout(0, accounts(5), U128(to_yocto("100")), 10);
+    assert_eq!(Some(&U128(to_yocto("2.5"))), payout.payout.get(&accounts(2)), "Charlie should get 25% of the royalty.");
+    assert_eq!(None, payout.payout.get(&group("band")), "The group itself should not be in the payout.");
+}
+
+/// A group can not contain itself, not even through another group
+#[test]
+#[should_panic(expected = "contains itself!")]
+fn payee_group_cycle_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    set_group(&mut context, &mut contract, group("horns"), vec![(group("band"), 10000)]);
+    set_group(&mut context, &mut contract, group("band"), vec![(accounts(2), 5000), (group("horns"), 5000)]);
+}
+
+/// Groups can only be nested MAX_PAYEE_GROUP_DEPTH levels deep
+#[test]
+#[should_panic(expected = "Payee groups can not be nested deeper than 4 levels!")]
+fn payee_group_depth_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    set_group(&mut context, &mut contract, group("level-0"), vec![(accounts(2), 10000)]);
+    for level in 1..5 {
+        set_group(&mut context, &mut contract, group(&format!("level-{}", level)), vec![(group(&format!("level-{}", level - 1)), 10000)]);
+    }
+}
+
+/// Only the owner of the group can update it
+#[test]
+#[should_panic(expected = "Only the owner can alter the payee group!")]
+fn update_payee_group_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob
+    set_group(&mut context, &mut contract, group("band"), vec![(accounts(1), 10000)]);
+}
+
+/// The ID of the group has to be a sub-account of the DAO, otherwise it could take the payments of a real account
+#[test]
+#[should_panic(expected = "The ID of a payee group has to be a sub-account of the DAO")]
+fn payee_group_outside_namespace_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    set_group(&mut context, &mut contract, accounts(1), vec![(accounts(0), 10000)]);
+}
+
+/// Updating a group can make the groups that contain it too deep, those are checked too
+#[test]
+#[should_panic(expected = "Payee groups can not be nested deeper than 4 levels!")]
+fn update_nested_payee_group_depth_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    set_group(&mut context, &mut contract, group("extra"), vec![(accounts(3), 10000)]);
+    set_group(&mut context, &mut contract, group("level-0"), vec![(accounts(2), 10000)]);
+    for level in 1..4 {
+        set_group(&mut context, &mut contract, group(&format!("level-{}", level)), vec![(group(&format!("level-{}", level - 1)), 10000)]);
+    }
+    set_group(&mut context, &mut contract, group("level-0"), vec![(group("extra"), 10000)]);     // level-3 would be 5 levels deep
+}
+
+/// Saves a group that contains itself, without the checks of `internal_set_payee_group()`
+fn insert_invalid_band(contract: &mut Contract) {
+    let members: HashMap<AccountId, u64> = vec![(accounts(2), 5000), (group("band"), 5000)].into_iter().collect();
+    contract.payee_groups.insert(&group("band"), &PayeeGroup {
+        owner: accounts(0),
+        revenue_table: RevenueTable::new(members, 15).unwrap(),
+    });
+}
+
+/// If the revenue can not be split, the sale is still recorded, and the income stays in the balance of the song
+#[test]
+fn sale_with_invalid_payee_group() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+    insert_invalid_band(&mut contract);
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(1, income_table.sold, "The sale should be recorded.");
+    assert_eq!(to_yocto("5"), income_table.current_balance, "The income should stay in the balance of the song.");
+    assert_eq!(0, income_table.revenue_split, "Nothing was split.");
+    assert_eq!(U128(0), contract.get_claimable(accounts(0)), "Nothing should be credited to Alice.");
+}
+
+/// The payout of a song that can not be split fails, the balance stays in the IncomeTable
+#[test]
+fn payout_with_invalid_payee_group() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_band(&mut context);
+    insert_invalid_band(&mut contract);
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.current_balance = to_yocto("4");
+    contract.income_tables.insert(&0, &income_table);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert!(matches!(progress.results[0], (0, PayoutOutcome::SplitFailed { .. })), "The payout should fail.");
+    assert_eq!(to_yocto("4"), contract.income_tables.get(&0).unwrap().current_balance, "The balance should stay in the IncomeTable.");
+    assert_eq!(0, contract.get_payout_queue(0, 10).len(), "Nothing should be queued.");
+}
@@ -1,19 +1,121 @@
-/*use near_sdk::test_utils::{accounts, VMContextBuilder};
-pub use near_sdk::json_types::{Base64VecU8, U128};
-use std::collections::{HashMap};
-use near_sdk::{log, testing_env, AccountId};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, Gas};
+use near_sdk_sim::to_yocto;
 use crate::fonoroot_tests::helpers::{
-    create_master_group_proposal, 
+    setup_contract_with_minted_song,
     add_member_to_master_proposal,
-    prepare_nft_half_ready_proposal,
-    update_nft_full_proposal,
-    mint_root_proposal,
-    add_revenue_table_proposal,
-    alter_revenue_table_proposal
+    payout_revenue_proposal
 };
-use near_sdk_sim::to_yocto;
-use crate::policy::{VersionedPolicy};
-use crate::types::{Action, MintRootResult};
+use crate::types::{Action, PayoutOutcome};
 use crate::Contract;
-use crate::Config;
-*/
\ No newline at end of file
+
+
+/// Song 0 of Alice is minted, and 4 NEAR is waiting for PayoutRevenue
+fn setup_contract_with_balance(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.current_balance = to_yocto("4");
+    contract.income_tables.insert(&0, &income_table);
+    contract
+}
+
+/// Every song of the list gets a result, and the results are saved for the proposal
+#[test]
+fn payout_revenue_results() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_balance(&mut context);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0, 0, 7]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert!(progress.finished, "The payout should be finished.");
+    assert_eq!(accounts(0), progress.executor, "Alice executed the proposal.");
+    assert_eq!(
+        vec![
+            (0, PayoutOutcome::Paid { amount: U128(to_yocto("4")), ft_amounts: vec![] }),
+            (0, PayoutOutcome::ZeroBalance),
+            (7, PayoutOutcome::Skipped),
+        ],
+        progress.results,
+        "The song should be paid once, and the song that does not exist should be skipped."
+    );
+    assert_eq!(0, contract.income_tables.get(&0).unwrap().current_balance, "The balance should be paid out.");
+}
+
+/// The NEP-141 balances that were paid out are saved in the result too
+#[test]
+fn payout_revenue_results_with_ft_balances() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_balance(&mut context);
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.ft_balances.insert(AccountId::new_unchecked("usdc.near".to_string()), 5_000_000);
+    income_table.ft_balances.insert(AccountId::new_unchecked("dai.near".to_string()), 0);
+    contract.income_tables.insert(&0, &income_table);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert_eq!(
+        vec![(0, PayoutOutcome::Paid {
+            amount: U128(to_yocto("4")),
+            ft_amounts: vec![(AccountId::new_unchecked("usdc.near".to_string()), U128(5_000_000))]
+        })],
+        progress.results,
+        "The USDC balance should be in the result, the empty DAI balance should not."
+    );
+}
+
+/// Members of the master group can't pay out songs of other Artists
+#[test]
+fn payout_revenue_not_authorized() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_balance(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob adds Charlie to the master group
+    let mut id = add_member_to_master_proposal(&mut context, &mut contract, accounts(2), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).build());     // Charlie
+    id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert_eq!(vec![(0, PayoutOutcome::NotAuthorized)], progress.results, "Charlie is not the owner of song 0.");
+    assert_eq!(to_yocto("4"), contract.income_tables.get(&0).unwrap().current_balance, "The balance should not change.");
+}
+
+/// If the gas runs out, the payout stops cleanly, and anyone can continue it
+#[test]
+fn payout_revenue_continues_after_gas_ran_out() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_balance(&mut context);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    testing_env!(context.prepaid_gas(Gas(10_000_000_000_000)).build());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let progress = contract.get_proposal(id).payout_progress.unwrap();
+    assert!(!progress.finished, "The payout should not be finished.");
+    assert_eq!(0, progress.cursor, "No song should be paid out.");
+
+    testing_env!(context.predecessor_account_id(accounts(3)).signer_account_id(accounts(3)).prepaid_gas(Gas(300_000_000_000_000)).build());   // Danny
+    let progress = contract.continue_payout_revenue(id);
+    assert!(progress.finished, "The payout should be finished.");
+    assert_eq!(vec![(0, PayoutOutcome::Paid { amount: U128(to_yocto("4")), ft_amounts: vec![] })], progress.results, "Song 0 should be paid out, with the rights of Alice.");
+}
+
+/// A finished payout can't be continued
+#[test]
+#[should_panic(expected = "The payout is already finished!")]
+fn continue_finished_payout_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_balance(&mut context);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract.continue_payout_revenue(id);
+}
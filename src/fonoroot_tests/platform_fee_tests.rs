@@ -0,0 +1,157 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    update_policy_parameters_proposal,
+    withdraw_platform_fees_proposal
+};
+use crate::proposals::{PolicyParameters};
+use crate::types::{Action, PlatformFees};
+use crate::Contract;
+
+
+/// Bob (Council) sets the platform fee
+fn set_platform_fee(context: &mut VMContextBuilder, contract: &mut Contract, platform_fee: u32) {
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = update_policy_parameters_proposal(context, contract, PolicyParameters {
+        proposal_bond: None,
+        proposal_period: None,
+        bounty_bond: None,
+        bounty_forgiveness_period: None,
+        max_revenue_table_length: None,
+        max_payout_transfers: None,
+        platform_fee: Some(platform_fee),
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Song 0 of Alice is minted, the platform fee is 2.5%, and Charlie bought the NFT for 5 NEAR
+fn setup_contract_with_sale(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+    set_platform_fee(context, &mut contract, 250);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    contract
+}
+
+/// The platform fee is deducted from the price, and the rest is the income of the song
+#[test]
+fn platform_fee_is_deducted() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_sale(&mut context);
+
+    let fee = to_yocto("5") / 40;
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(to_yocto("5") - fee, income_table.total_income, "The income of the song should not contain the fee.");
+    assert_eq!(
+        to_yocto("5") - fee,
+        contract.get_claimable(accounts(0)).0 + contract.get_claimable(accounts(1)).0,
+        "The beneficiaries should get the price without the fee."
+    );
+    assert_eq!(
+        PlatformFees { fee: 250, balance: U128(fee), total: U128(fee), ft_balances: HashMap::new(), ft_total: HashMap::new() },
+        contract.get_platform_fees(),
+        "The fee should be in the DAO treasury."
+    );
+    assert_eq!(U128(fee), contract.get_treasury_reconciliation().platform_fees, "The fees should be part of the reconciliation.");
+}
+
+/// Without a platform fee, the whole price is the income of the song
+#[test]
+fn no_platform_fee_by_default() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    assert_eq!(to_yocto("5"), contract.income_tables.get(&0).unwrap().total_income, "The whole price should go to the song.");
+    assert_eq!(PlatformFees { fee: 0, balance: U128(0), total: U128(0), ft_balances: HashMap::new(), ft_total: HashMap::new() }, contract.get_platform_fees(), "There should be no fees.");
+}
+
+/// The Council can withdraw the collected fees, the total is kept
+#[test]
+fn withdraw_platform_fees() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_sale(&mut context);
+    let fee = to_yocto("5") / 40;
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = withdraw_platform_fees_proposal(&mut context, &mut contract, accounts(4), U128(fee / 2), None);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let fees = contract.get_platform_fees();
+    assert_eq!(U128(fee - fee / 2), fees.balance, "Half of the fees should be withdrawn.");
+    assert_eq!(U128(fee), fees.total, "The total should not change.");
+}
+
+/// More than the balance can't be withdrawn
+#[test]
+#[should_panic(expected = "The amount is more than the platform fee balance!")]
+fn withdraw_too_much_platform_fees_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_sale(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = withdraw_platform_fees_proposal(&mut context, &mut contract, accounts(4), U128(to_yocto("1")), None);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// The platform fee is deducted from sales in NEP-141 tokens too, it is kept per token
+#[test]
+fn ft_platform_fee_is_deducted() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_platform_fee(&mut context, &mut contract, 250);
+    let usdc = AccountId::new_unchecked("usdc.near".to_string());
+
+    let refund = contract.ft_buy_nft_callback(Ok(true.into()), 0, accounts(2), usdc.clone(), U128(6_000_000), U128(5_000_000), 0);
+    assert_eq!(U128(1_000_000), refund, "Only the difference should be refunded, the buyer pays the same price.");
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(Some(&4_875_000), income_table.ft_balances.get(&usdc), "The USDC balance of the song should not contain the fee.");
+    assert_eq!(Some(&4_875_000), income_table.ft_total_income.get(&usdc), "The USDC income of the song should not contain the fee.");
+    let fees = contract.get_platform_fees();
+    assert_eq!(Some(&U128(125_000)), fees.ft_balances.get(&usdc), "The fee should be in the USDC platform fee balance.");
+    assert_eq!(Some(&U128(125_000)), fees.ft_total.get(&usdc), "The fee should be in the USDC total.");
+    assert_eq!(U128(0), fees.balance, "The $NEAR fee balance should not change.");
+}
+
+/// The Council can withdraw the fees of a token
+#[test]
+fn withdraw_ft_platform_fees() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_platform_fee(&mut context, &mut contract, 250);
+    let usdc = AccountId::new_unchecked("usdc.near".to_string());
+    contract.ft_buy_nft_callback(Ok(true.into()), 0, accounts(2), usdc.clone(), U128(5_000_000), U128(5_000_000), 0);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = withdraw_platform_fees_proposal(&mut context, &mut contract, accounts(4), U128(100_000), Some(usdc.clone()));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let fees = contract.get_platform_fees();
+    assert_eq!(Some(&U128(25_000)), fees.ft_balances.get(&usdc), "100 000 should be withdrawn.");
+    assert_eq!(Some(&U128(125_000)), fees.ft_total.get(&usdc), "The total should not change.");
+}
+
+/// The fees of a token can't be withdrawn from the $NEAR fees, or from another token
+#[test]
+#[should_panic(expected = "The amount is more than the platform fee balance!")]
+fn withdraw_ft_platform_fees_other_token_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_sale(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());
+    let id = withdraw_platform_fees_proposal(&mut context, &mut contract, accounts(4), U128(1), Some(AccountId::new_unchecked("usdc.near".to_string())));
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// The platform fee has a maximum
+#[test]
+#[should_panic(expected = "The platform fee can not be more than 2000 basis points!")]
+fn platform_fee_too_high_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    set_platform_fee(&mut context, &mut contract, 2_001);
+}
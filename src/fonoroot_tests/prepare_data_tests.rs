@@ -46,13 +46,13 @@ fn prepare_data_ready_tests() {
     assert_eq!("Description about a Test NFT".to_string(), the_inserted_data.desc.unwrap(), "The Description is not correct!");
     assert_eq!(AccountId::new_unchecked("minting-contract-1.near".to_string()), the_inserted_data.contract, "The Minting Contract is not correctly set!");
     assert_eq!("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string(), the_inserted_data.image.unwrap(), "The Image CID is not correct!");
-    assert_eq!(Base64VecU8(vec![1,2,3]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
+    assert_eq!(Base64VecU8(vec![1; 32]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
     assert_eq!("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.music.unwrap(), "The Music Folder CID is not correct!");
-    assert_eq!(Base64VecU8(vec![4,5,6]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
+    assert_eq!(Base64VecU8(vec![4; 32]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
     assert_eq!("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.animation_url.unwrap(), "The Animation URL is not correct!");
-    assert_eq!(Base64VecU8(vec![7,8,9]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
+    assert_eq!(Base64VecU8(vec![7; 32]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
     assert_eq!("QmeCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVUHpb".to_string(), the_inserted_data.meta.unwrap(), "The Meta JSON CID is not correct!");
-    assert_eq!(Base64VecU8(vec![10,11,12]), the_inserted_data.meta_hash.unwrap(), "The Meta JSON hash is not correct!");
+    assert_eq!(Base64VecU8(vec![10; 32]), the_inserted_data.meta_hash.unwrap(), "The Meta JSON hash is not correct!");
 
     assert_eq!(0, the_inserted_data.id, "The ID of the newly inserted data should be 0.");
     assert_eq!(accounts(0), the_inserted_data.artist, "Artist (owner) should be Alice.");
@@ -173,11 +173,11 @@ fn prepare_data_half_tests() {
     assert!(the_inserted_data.desc.is_none(), "The Description field should be empty!");
     assert_eq!(AccountId::new_unchecked("minting-contract-1.near".to_string()), the_inserted_data.contract, "The Minting Contract is not correctly set!");
     assert_eq!("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string(), the_inserted_data.image.unwrap(), "The Image CID is not correct!");
-    assert_eq!(Base64VecU8(vec![1,2,3]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
+    assert_eq!(Base64VecU8(vec![1; 32]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
     assert_eq!("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.music.unwrap(), "The Music Folder CID is not correct!");
-    assert_eq!(Base64VecU8(vec![4,5,6]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
+    assert_eq!(Base64VecU8(vec![4; 32]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
     assert_eq!("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.animation_url.unwrap(), "The Animation URL is not correct!");
-    assert_eq!(Base64VecU8(vec![7,8,9]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
+    assert_eq!(Base64VecU8(vec![7; 32]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
     assert!(the_inserted_data.meta.is_none(), "The Meta JSON CID field should be empty");
     assert!(the_inserted_data.meta_hash.is_none(), "The Meta JSON hash field should be empty!");
 
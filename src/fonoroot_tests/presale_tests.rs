@@ -0,0 +1,102 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U64, U128};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_price_schedule_proposal,
+    update_presale_allowlist_proposal
+};
+use crate::types::{Action, PriceSchedule};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted, it has a presale until 1000 (3 NEAR, 5 NEAR after), and Charlie is on the allowlist
+fn setup_contract_with_presale(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    let mut id = set_price_schedule_proposal(context, &mut contract, 0, Some(PriceSchedule::Presale {
+        presale_end: U64(1000),
+        presale_price: U128(to_yocto("3")),
+        price: U128(to_yocto("5")),
+    }));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = update_presale_allowlist_proposal(context, &mut contract, 0, vec![accounts(2)], vec![]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// `buyer` tries to buy the NFT at `timestamp`, with `deposit`
+fn buy_as(context: &mut VMContextBuilder, contract: &mut Contract, buyer: AccountId, timestamp: u64, deposit: &str) {
+    testing_env!(context
+        .predecessor_account_id(buyer.clone())
+        .signer_account_id(buyer)
+        .attached_deposit(to_yocto(deposit))
+        .block_timestamp(timestamp)
+        .build()
+    );
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+}
+
+/// Accounts on the allowlist can buy during the presale, for the presale price
+#[test]
+fn presale_buy_allowlisted() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_presale(&mut context);
+
+    assert!(contract.is_on_presale_allowlist(0, accounts(2)), "Charlie should be on the allowlist.");
+    assert_eq!(vec![accounts(2)], contract.get_presale_allowlist(0, 0, 10), "Only Charlie should be on the allowlist.");
+
+    buy_as(&mut context, &mut contract, accounts(2), 500, "3.1");
+    let status = contract.get_sale_status(0);
+    assert!(status.presale, "The presale should be going on.");
+    assert_eq!(1, status.pending_sales, "Charlie's purchase should be in progress.");
+}
+
+/// Accounts that are not on the allowlist can't buy during the presale
+#[test]
+#[should_panic(expected = "Only accounts on the allowlist can buy during the presale!")]
+fn presale_buy_not_allowlisted_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_presale(&mut context);
+
+    buy_as(&mut context, &mut contract, accounts(3), 500, "3.1");
+}
+
+/// After the presale, anyone can buy
+#[test]
+fn buy_after_presale() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_presale(&mut context);
+
+    buy_as(&mut context, &mut contract, accounts(3), 1000, "5.1");
+    let status = contract.get_sale_status(0);
+    assert!(!status.presale, "The presale should be over.");
+    assert_eq!(1, status.pending_sales, "The purchase should be in progress.");
+}
+
+/// Accounts that were removed from the allowlist can't buy during the presale
+#[test]
+#[should_panic(expected = "Only accounts on the allowlist can buy during the presale!")]
+fn presale_buy_removed_from_allowlist_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_presale(&mut context);
+
+    let id = update_presale_allowlist_proposal(&mut context, &mut contract, 0, vec![], vec![accounts(2)]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert!(!contract.is_on_presale_allowlist(0, accounts(2)), "Charlie should not be on the allowlist.");
+
+    buy_as(&mut context, &mut contract, accounts(2), 500, "3.1");
+}
+
+/// Only the owner can update the allowlist
+#[test]
+#[should_panic(expected = "Only the owner (Artist) can alter the presale allowlist!")]
+fn update_presale_allowlist_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob is in the Council, but he is not the owner
+    let id = update_presale_allowlist_proposal(&mut context, &mut contract, 0, vec![accounts(1)], vec![]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
@@ -0,0 +1,134 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U64, U128};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_price_schedule_proposal
+};
+use crate::types::{Action, PriceSchedule, PriceStep};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted (price is 5 NEAR), and it has the price schedule
+fn setup_contract_with_schedule(context: &mut VMContextBuilder, schedule: PriceSchedule) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    let id = set_price_schedule_proposal(context, &mut contract, 0, Some(schedule));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// Current price of song 0 at `timestamp`
+fn price_at(context: &mut VMContextBuilder, contract: &Contract, timestamp: u64) -> Option<U128> {
+    testing_env!(context.block_timestamp(timestamp).build());
+    contract.get_price(AccountId::new_unchecked("minting-contract-1.near".to_string()), "fono-root-0".to_string())
+}
+
+/// The price changes at the steps, before the first step the first price is used
+#[test]
+fn stepped_price_schedule() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_schedule(&mut context, PriceSchedule::Stepped {
+        steps: vec![
+            PriceStep { from: U64(1000), price: U128(to_yocto("10")) },
+            PriceStep { from: U64(2000), price: U128(to_yocto("7")) },
+        ]
+    });
+
+    assert_eq!(Some(U128(to_yocto("10"))), price_at(&mut context, &contract, 500), "Before the first step, the first price should be used.");
+    assert_eq!(Some(U128(to_yocto("10"))), price_at(&mut context, &contract, 1500), "The first price should be used.");
+    assert_eq!(Some(U128(to_yocto("7"))), price_at(&mut context, &contract, 2000), "The second price should be used.");
+}
+
+/// The price of a dutch auction goes down linearly
+#[test]
+fn dutch_auction_price_schedule() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_schedule(&mut context, PriceSchedule::DutchAuction {
+        start: U64(1000),
+        end: U64(2000),
+        start_price: U128(to_yocto("10")),
+        end_price: U128(to_yocto("2")),
+    });
+
+    assert_eq!(Some(U128(to_yocto("10"))), price_at(&mut context, &contract, 500), "Before the auction, the start price should be used.");
+    assert_eq!(Some(U128(to_yocto("6"))), price_at(&mut context, &contract, 1500), "In the middle of the auction, the price should be 6 NEAR.");
+    assert_eq!(Some(U128(to_yocto("2"))), price_at(&mut context, &contract, 3000), "After the auction, the end price should be used.");
+}
+
+/// The presale price is used until the end of the presale
+#[test]
+fn presale_price_schedule() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_schedule(&mut context, PriceSchedule::Presale {
+        presale_end: U64(1000),
+        presale_price: U128(to_yocto("3")),
+        price: U128(to_yocto("8")),
+    });
+
+    assert_eq!(Some(U128(to_yocto("3"))), price_at(&mut context, &contract, 999), "The presale price should be used.");
+    assert_eq!(Some(U128(to_yocto("8"))), price_at(&mut context, &contract, 1000), "The normal price should be used.");
+}
+
+/// Without a schedule, the price of the IncomeTable is used again
+#[test]
+fn remove_price_schedule() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_schedule(&mut context, PriceSchedule::Fixed { price: U128(to_yocto("1")) });
+    assert_eq!(Some(U128(to_yocto("1"))), price_at(&mut context, &contract, 0), "The price of the schedule should be used.");
+
+    let id = set_price_schedule_proposal(&mut context, &mut contract, 0, None);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(Some(U128(to_yocto("5"))), price_at(&mut context, &contract, 0), "The price should be 5 NEAR again.");
+}
+
+/// buy_nft uses the current price, and the callback credits the price that was paid
+#[test]
+fn buy_nft_with_price_schedule() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_schedule(&mut context, PriceSchedule::Fixed { price: U128(to_yocto("2")) });
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).attached_deposit(to_yocto("2.1")).build());
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("2")), 0);
+
+    assert_eq!(to_yocto("2"), contract.get_single_income_table(0).total_income, "The total income should be 2 NEAR.");
+}
+
+/// buy_nft fails if the deposit is less than the current price
+#[test]
+#[should_panic(expected = "Not enough deposit was attached to buy the NFT")]
+fn buy_nft_less_than_current_price_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_schedule(&mut context, PriceSchedule::Fixed { price: U128(to_yocto("8")) });
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).attached_deposit(to_yocto("5")).build());
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+}
+
+/// The steps have to be in increasing order
+#[test]
+#[should_panic(expected = "The steps of the price schedule have to be in increasing order!")]
+fn stepped_price_schedule_order_error() {
+    let mut context = VMContextBuilder::new();
+    setup_contract_with_schedule(&mut context, PriceSchedule::Stepped {
+        steps: vec![
+            PriceStep { from: U64(2000), price: U128(to_yocto("10")) },
+            PriceStep { from: U64(1000), price: U128(to_yocto("7")) },
+        ]
+    });
+}
+
+/// The price of a dutch auction can't go up
+#[test]
+#[should_panic(expected = "The price of a dutch auction can not go up!")]
+fn dutch_auction_price_up_error() {
+    let mut context = VMContextBuilder::new();
+    setup_contract_with_schedule(&mut context, PriceSchedule::DutchAuction {
+        start: U64(1000),
+        end: U64(2000),
+        start_price: U128(to_yocto("2")),
+        end_price: U128(to_yocto("10")),
+    });
+}
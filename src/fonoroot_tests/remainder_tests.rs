@@ -0,0 +1,123 @@
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{env, testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_payee_group_proposal,
+    payout_revenue_proposal
+};
+use crate::types::{Action, RevenueTable};
+
+
+/// RevenueTable from a list of shares
+fn table(shares: Vec<(AccountId, u64)>) -> RevenueTable {
+    RevenueTable::new(shares.into_iter().collect(), 15).unwrap()
+}
+
+/// The rounding remainder goes to the largest share
+#[test]
+fn remainder_goes_to_largest_share() {
+    let parts = table(vec![(accounts(0), 3333), (accounts(1), 3333), (accounts(2), 3334)]).split(10);
+    assert_eq!(10, parts.values().sum::<u128>(), "The parts should add up to the amount.");
+    assert_eq!(Some(&4), parts.get(&accounts(2)), "Charlie has the largest share, so Charlie gets the remainder.");
+}
+
+/// If the largest shares are the same, the remainder goes to the smallest AccountId
+#[test]
+fn remainder_tie_is_deterministic() {
+    let parts = table(vec![(accounts(1), 5000), (accounts(0), 5000)]).split(3);
+    assert_eq!(Some(&2), parts.get(&accounts(0)), "alice is smaller than bob, so Alice gets the remainder.");
+    assert_eq!(Some(&1), parts.get(&accounts(1)), "Bob gets his rounded part.");
+}
+
+/// Nothing is lost when a payee group is split further
+#[test]
+fn payout_object_is_exact_with_payee_groups() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    let band = AccountId::new_unchecked(format!("band.{}", env::current_account_id()));
+    let id = set_payee_group_proposal(&mut context, &mut contract, band.clone(), vec![(accounts(2), 3333), (accounts(3), 3333), (accounts(4), 3334)].into_iter().collect());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let payout = contract.generate_payout_object(table(vec![(accounts(0), 3333), (band, 6667)]), 1001, 10);
+    assert_eq!(1001, payout.payout.values().map(|amount| amount.0).sum::<u128>(), "The payout should add up to the price.");
+}
+
+/// After a payout, every yoctoNEAR of the balance is sent or waiting in the payout queue
+#[test]
+fn payout_keeps_accounting_exact() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);   // Alice 90%, Bob 10%
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.current_balance = 19;
+    contract.income_tables.insert(&0, &income_table);
+
+    let id = payout_revenue_proposal(&mut context, &mut contract, vec![0]);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let sent: u128 = get_logs()
+        .iter()
+        .filter(|log| log.starts_with("Sending "))
+        .map(|log| log.split(' ').nth(1).unwrap().parse::<u128>().unwrap())
+        .sum();
+    let queued: u128 = contract.get_payout_queue(0, 10).iter().map(|(_, transfer)| transfer.amount.0).sum();
+    assert_eq!(19, sent + queued, "Every yoctoNEAR of the balance should be paid out.");
+    assert_eq!(queued, contract.queued_revenue, "The queued revenue should be tracked.");
+}
+
+/// The parts of the contract balance add up to the balance
+#[test]
+fn treasury_reconciliation_adds_up() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    let mut income_table = contract.income_tables.get(&0).unwrap();
+    income_table.current_balance = to_yocto("4");
+    contract.income_tables.insert(&0, &income_table);
+    contract.total_claimable = to_yocto("1");
+    contract.claimable_revenue.insert(&accounts(1), &to_yocto("1"));
+
+    testing_env!(context.account_balance(to_yocto("100")).build());
+    let reconciliation = contract.get_treasury_reconciliation();
+    assert_eq!(U128(to_yocto("4")), reconciliation.song_balances, "The balance of the song should be counted.");
+    assert_eq!(U128(to_yocto("1")), reconciliation.claimable, "The claimable revenue should be counted.");
+    assert_eq!(
+        reconciliation.contract_balance.0,
+        reconciliation.storage_cost.0 + reconciliation.locked_amount.0 + reconciliation.song_balances.0 + reconciliation.claimable.0
+            + reconciliation.queued_payouts.0 + reconciliation.failed_transactions.0 + reconciliation.platform_fees.0
+            + reconciliation.draft_storage.0 + reconciliation.pending_purchases.0 + reconciliation.unallocated.0,
+        "The parts should add up to the contract balance."
+    );
+    assert!(reconciliation.is_covered, "The contract has enough balance.");
+
+    testing_env!(context.account_balance(to_yocto("3")).build());
+    let reconciliation = contract.get_treasury_reconciliation();
+    assert!(!reconciliation.is_covered, "The contract owes more than its balance.");
+    assert_eq!(U128(0), reconciliation.unallocated, "Nothing is unallocated.");
+}
+
+/// Prepaid draft storage and the deposits of purchases in progress are owed to the Artists and to the buyers, they are not unallocated
+#[test]
+fn treasury_reconciliation_counts_deposits() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+    let alice_balance = contract.get_draft_storage_balance(accounts(0)).0;                     // What is left from minting the song
+
+    testing_env!(context.predecessor_account_id(accounts(3)).signer_account_id(accounts(3)).attached_deposit(to_yocto("2")).build());   // Danny
+    contract.draft_storage_deposit();
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).attached_deposit(to_yocto("5.1")).build());  // Charlie
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+
+    testing_env!(context.account_balance(to_yocto("7")).attached_deposit(0).build());
+    let reconciliation = contract.get_treasury_reconciliation();
+    assert_eq!(U128(alice_balance + to_yocto("2")), reconciliation.draft_storage, "The draft storage balance of Danny should be counted.");
+    assert_eq!(U128(to_yocto("5.1")), reconciliation.pending_purchases, "The price and the storage deposit of Charlie should be counted.");
+    assert!(!reconciliation.is_covered, "The contract can't pay back the deposits and its own storage.");
+
+    contract.buy_nft_callback(Ok(false.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    testing_env!(context.predecessor_account_id(accounts(3)).signer_account_id(accounts(3)).build());
+    contract.draft_storage_withdraw(None);
+    let reconciliation = contract.get_treasury_reconciliation();
+    assert_eq!(U128(alice_balance), reconciliation.draft_storage, "Danny withdrew the draft storage balance.");
+    assert_eq!(U128(0), reconciliation.pending_purchases, "The purchase is finished.");
+}
@@ -0,0 +1,91 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId, PromiseError};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song
+};
+use crate::types::{FailedTransactionStatus};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted, and sending 5 NEAR to Charlie (failed transaction 0) and 2 NEAR to Danny (failed transaction 1) failed
+fn setup_contract_with_failed_transactions(context: &mut VMContextBuilder) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.transfer_callback(Err(PromiseError::Failed), accounts(2), U128(to_yocto("5")), None);
+    contract.transfer_callback(Err(PromiseError::Failed), accounts(3), U128(to_yocto("2")), None);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).signer_account_id(accounts(2)).build());     // Charlie
+    contract
+}
+
+/// Collaborators can list the failed transactions that belong to them
+#[test]
+fn get_failed_transactions_for_test() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_failed_transactions(&mut context);
+
+    let failed = contract.get_failed_transactions_for(accounts(3));
+    assert_eq!(1, failed.len(), "Danny should have 1 failed transaction.");
+    assert_eq!(1, failed[0].0, "The ID should be 1.");
+    assert_eq!(to_yocto("2"), failed[0].1.amount, "The amount should be 2 NEAR.");
+}
+
+/// The beneficiary can retry, the record is Retried while the transfer is in flight, and Settled after
+#[test]
+fn retry_failed_transaction_test() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transactions(&mut context);
+
+    contract.retry_failed_transaction(0, None);
+    assert_eq!(FailedTransactionStatus::Retried, contract.get_failed_transaction(0).unwrap().status, "The transaction should be Retried while the transfer is in flight.");
+    assert_eq!(1, contract.get_failed_transactions_for(accounts(2)).len(), "The amount is owed until the transfer succeeds.");
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.resend_failed_transaction_callback(Ok(()), 0, accounts(2));
+    assert_eq!(FailedTransactionStatus::Settled, contract.get_failed_transaction(0).unwrap().status, "The transaction should be Settled.");
+    assert!(contract.get_failed_transactions_for(accounts(2)).is_empty(), "Nothing is owed to Charlie.");
+}
+
+/// If the retry fails, the record is Pending again with the same ID, for the original beneficiary
+#[test]
+fn retry_failed_transaction_fails_again() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transactions(&mut context);
+
+    contract.retry_failed_transaction(0, Some(accounts(4)));
+
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.resend_failed_transaction_callback(Err(PromiseError::Failed), 0, accounts(4));
+
+    let failed = contract.get_failed_transactions_for(accounts(2));
+    assert_eq!(1, failed.len(), "The record should be kept.");
+    assert_eq!(FailedTransactionStatus::Pending, failed[0].1.status, "The transaction should be Pending again.");
+    assert_eq!(accounts(2), failed[0].1.beneficiary, "Charlie should be the beneficiary.");
+    assert_eq!(0, failed[0].0, "The ID should not change.");
+    assert_eq!(to_yocto("5"), failed[0].1.amount, "The amount should not change.");
+    assert_eq!(2, contract.failed_nonce, "No new failed transaction should be created.");
+}
+
+/// Only the beneficiary can retry
+#[test]
+#[should_panic(expected = "Only the beneficiary can retry the failed transaction!")]
+fn retry_failed_transaction_not_beneficiary_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transactions(&mut context);
+
+    contract.retry_failed_transaction(1, Some(accounts(2)));
+}
+
+/// A transaction that is already being retried can not be retried again
+#[test]
+#[should_panic(expected = "Only pending failed transactions can be sent again!")]
+fn retry_failed_transaction_twice_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_failed_transactions(&mut context);
+
+    contract.retry_failed_transaction(0, None);
+    contract.retry_failed_transaction(0, None);
+}
@@ -0,0 +1,87 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::testing_env;
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_royalty_proposal
+};
+use crate::types::{Action};
+
+
+/// Without royalty, the seller gets everything
+#[test]
+fn nft_payout_without_royalty() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_minted_song(&mut context);
+
+    let payout = contract.nft_payout(0, accounts(2), U128(to_yocto("100")), 10);
+    assert_eq!(1, payout.payout.len(), "Only the seller should be paid.");
+    assert_eq!(Some(&U128(to_yocto("100"))), payout.payout.get(&accounts(2)), "Charlie should get everything.");
+}
+
+/// The royalty is split according to the RevenueTable (Alice 90%, Bob 10%), the seller gets the rest
+#[test]
+fn nft_payout_with_royalty() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 1000);                        // 10%
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(1000, contract.get_single_income_table(0).royalty, "The royalty should be 10%.");
+
+    let payout = contract.nft_payout(0, accounts(2), U128(to_yocto("100")), 10);
+    assert_eq!(Some(&U128(to_yocto("9"))), payout.payout.get(&accounts(0)), "Alice should get 90% of the royalty.");
+    assert_eq!(Some(&U128(to_yocto("1"))), payout.payout.get(&accounts(1)), "Bob should get 10% of the royalty.");
+    assert_eq!(Some(&U128(to_yocto("90"))), payout.payout.get(&accounts(2)), "Charlie (the seller) should get the rest.");
+}
+
+/// If the seller is also a beneficiary, the parts are added together
+#[test]
+fn nft_payout_seller_is_beneficiary() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 1000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let payout = contract.nft_payout(0, accounts(0), U128(to_yocto("100")), 2);
+    assert_eq!(2, payout.payout.len(), "Alice and Bob should be paid.");
+    assert_eq!(Some(&U128(to_yocto("99"))), payout.payout.get(&accounts(0)), "Alice should get the price and her part of the royalty.");
+}
+
+/// The payout has to fit into max_len_payout
+#[test]
+#[should_panic(expected = "The contract cannot payout to that many receivers")]
+fn nft_payout_max_len_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 1000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    contract.nft_payout(0, accounts(2), U128(to_yocto("100")), 2);                            // Alice, Bob and Charlie would be 3
+}
+
+/// The royalty can't be more than MAX_ROYALTY
+#[test]
+#[should_panic(expected = "The royalty can not be more than 5000 basis points!")]
+fn set_royalty_too_high_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 5001);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Only the owner can set the royalty
+#[test]
+#[should_panic(expected = "Only the owner (Artist) can alter the royalty!")]
+fn set_royalty_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob is in the Council, but he is not the owner
+    let id = set_royalty_proposal(&mut context, &mut contract, 0, 1000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
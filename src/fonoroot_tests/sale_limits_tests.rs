@@ -0,0 +1,140 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U64, U128};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_sale_limits_proposal
+};
+use crate::types::{Action, SaleLimits};
+use crate::Contract;
+
+
+/// Song 0 of Alice is minted, it has a sale window (1000 - 2000) and an editions cap
+fn setup_contract_with_limits(context: &mut VMContextBuilder, max_editions: Option<u64>) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    let id = set_sale_limits_proposal(context, &mut contract, 0, SaleLimits {
+        start: Some(U64(1000)),
+        end: Some(U64(2000)),
+        max_editions: max_editions,
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// Charlie tries to buy the NFT at `timestamp`
+fn buy_as_charlie(context: &mut VMContextBuilder, contract: &mut Contract, timestamp: u64) {
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .signer_account_id(accounts(2))
+        .attached_deposit(to_yocto("5.1"))
+        .block_timestamp(timestamp)
+        .build()
+    );
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+}
+
+/// Without limits, the NFT is on sale, and there is no cap
+#[test]
+fn sale_status_without_limits() {
+    let mut context = VMContextBuilder::new();
+    let contract = setup_contract_with_minted_song(&mut context);
+
+    let status = contract.get_sale_status(0);
+    assert!(status.on_sale, "The NFT should be on sale.");
+    assert_eq!(None, status.remaining, "There should be no cap.");
+    assert_eq!(0, status.sold, "Nothing was sold yet.");
+}
+
+/// A purchase reserves an edition, the callback turns it into a sale
+#[test]
+fn buy_nft_counts_editions() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_limits(&mut context, Some(1));
+
+    buy_as_charlie(&mut context, &mut contract, 1500);
+    let status = contract.get_sale_status(0);
+    assert_eq!(1, status.pending_sales, "The purchase should be in progress.");
+    assert_eq!(Some(0), status.remaining, "The only edition should be reserved.");
+    assert!(!status.on_sale, "The NFT should not be on sale while the edition is reserved.");
+
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    let status = contract.get_sale_status(0);
+    assert_eq!(1, status.sold, "1 NFT should be sold.");
+    assert_eq!(0, status.pending_sales, "There should be no purchase in progress.");
+    assert_eq!(Some(0), status.remaining, "The NFT should be sold out.");
+}
+
+/// If the purchase failed, the edition is released
+#[test]
+fn buy_nft_failed_releases_edition() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_limits(&mut context, Some(1));
+
+    buy_as_charlie(&mut context, &mut contract, 1500);
+    contract.buy_nft_callback(Ok(false.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+
+    let status = contract.get_sale_status(0);
+    assert_eq!(0, status.sold, "Nothing should be sold.");
+    assert_eq!(Some(1), status.remaining, "The edition should be available again.");
+}
+
+/// No more NFTs can be bought after the cap is reached
+#[test]
+#[should_panic(expected = "The NFT is sold out!")]
+fn buy_nft_sold_out_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_limits(&mut context, Some(1));
+
+    buy_as_charlie(&mut context, &mut contract, 1500);
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+    buy_as_charlie(&mut context, &mut contract, 1600);
+}
+
+/// The NFT can't be bought before the sale window
+#[test]
+#[should_panic(expected = "The sale has not started yet!")]
+fn buy_nft_before_start_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_limits(&mut context, None);
+
+    buy_as_charlie(&mut context, &mut contract, 500);
+}
+
+/// The NFT can't be bought after the sale window
+#[test]
+#[should_panic(expected = "The sale has ended!")]
+fn buy_nft_after_end_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_limits(&mut context, None);
+
+    buy_as_charlie(&mut context, &mut contract, 2000);
+}
+
+/// The end of the sale window has to be after the start
+#[test]
+#[should_panic(expected = "The end of the sale has to be after the start!")]
+fn set_sale_limits_wrong_window_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    let id = set_sale_limits_proposal(&mut context, &mut contract, 0, SaleLimits {
+        start: Some(U64(2000)),
+        end: Some(U64(1000)),
+        max_editions: None,
+    });
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Only the owner can set the sale limits
+#[test]
+#[should_panic(expected = "Only the owner (Artist) can alter the sale limits!")]
+fn set_sale_limits_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob is in the Council, but he is not the owner
+    let id = set_sale_limits_proposal(&mut context, &mut contract, 0, SaleLimits::default());
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
@@ -0,0 +1,77 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env, AccountId};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song
+};
+use crate::types::{VaultResult};
+use crate::Contract;
+
+
+/// `buyer` buys song 0 (price is 5 NEAR) at `timestamp`, and the minting contract returns `result`
+fn buy(context: &mut VMContextBuilder, contract: &mut Contract, buyer: AccountId, timestamp: u64, result: VaultResult) {
+    testing_env!(context
+        .predecessor_account_id(buyer.clone())
+        .signer_account_id(buyer.clone())
+        .attached_deposit(to_yocto("5.1"))
+        .block_timestamp(timestamp)
+        .build()
+    );
+    contract.buy_nft("fono-root-0".to_string(), AccountId::new_unchecked("minting-contract-1.near".to_string()));
+    let purchase_id = contract.purchase_nonce - 1;
+    testing_env!(context.predecessor_account_id(AccountId::new_unchecked("dao.near".to_string())).build());
+    contract.buy_nft_callback(Ok(result), 0, buyer, U128(to_yocto("5")), purchase_id);
+}
+
+/// Successful sales are added to the sales log of the song, with the NFT ID from the minting contract
+#[test]
+fn sales_log_records_sales() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    buy(&mut context, &mut contract, accounts(2), 1000, VaultResult::Detailed { success: true, storage_used: None, token_id: Some("fono-root-0-1".to_string()) });
+    buy(&mut context, &mut contract, accounts(3), 2000, true.into());
+
+    let sales = contract.get_sales(0, 0, 10);
+    assert_eq!(2, sales.len(), "There should be 2 sales.");
+    assert_eq!(accounts(2), sales[0].buyer, "Charlie should be the first buyer.");
+    assert_eq!(U128(to_yocto("5")), sales[0].price, "The price should be 5 NEAR.");
+    assert_eq!(None, sales[0].token_id, "The NFT was bought with $NEAR.");
+    assert_eq!(Some("fono-root-0-1".to_string()), sales[0].nft_id, "The NFT ID should come from the minting contract.");
+    assert_eq!(1000, sales[0].timestamp, "The timestamp should be 1000.");
+    assert_eq!(accounts(3), sales[1].buyer, "Danny should be the second buyer.");
+    assert_eq!(None, sales[1].nft_id, "The minting contract didn't report the NFT ID.");
+
+    assert_eq!(1, contract.get_sales(0, 1, 10).len(), "Pagination should skip the first sale.");
+}
+
+/// Failed purchases are not in the sales log
+#[test]
+fn sales_log_ignores_failed_purchases() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    buy(&mut context, &mut contract, accounts(2), 1000, false.into());
+
+    assert!(contract.get_sales(0, 0, 10).is_empty(), "There should be no sales.");
+    assert!(contract.get_purchases_by_buyer(accounts(2), 0, 10).is_empty(), "Charlie should not have purchases.");
+}
+
+/// The purchases of a buyer can be listed
+#[test]
+fn purchases_by_buyer() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    buy(&mut context, &mut contract, accounts(2), 1000, true.into());
+    buy(&mut context, &mut contract, accounts(3), 2000, true.into());
+    buy(&mut context, &mut contract, accounts(2), 3000, true.into());
+
+    let purchases = contract.get_purchases_by_buyer(accounts(2), 0, 10);
+    assert_eq!(2, purchases.len(), "Charlie should have 2 purchases.");
+    assert_eq!(0, purchases[0].0, "The TreeIndex should be 0.");
+    assert_eq!(1000, purchases[0].1.timestamp, "The first purchase should be at 1000.");
+    assert_eq!(3000, purchases[1].1.timestamp, "The second purchase should be at 3000.");
+    assert_eq!(1, contract.get_purchases_by_buyer(accounts(3), 0, 10).len(), "Danny should have 1 purchase.");
+}
@@ -0,0 +1,256 @@
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env};
+use crate::fonoroot_tests::helpers::{
+    create_master_group_proposal,
+    add_member_to_master_proposal,
+    remove_member_from_master_proposal,
+    prepare_nft_full_proposal,
+    prepare_nft_half_ready_proposal,
+    schedule_mint_proposal,
+    mint_root_proposal
+};
+use crate::policy::{VersionedPolicy};
+use crate::types::{Action};
+use crate::Contract;
+use crate::Config;
+
+
+/// Scheduling a ready draft will store the release time, and the draft will be on the scheduled list
+#[test]
+fn schedule_mint_stores_release_time() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert_eq!(Some(1_000), contract.in_progress_nfts.get(&0).unwrap().scheduled, "The release time should be stored in the draft.");
+    assert!(contract.scheduled_mints.iter().any(|((_, id), _)| id == 0), "The draft should be on the scheduled list.");
+    assert_eq!(1, contract.get_scheduled_mints(0, 10).len(), "There should be exactly 1 scheduled mint.");
+}
+
+/// execute_scheduled_mints() should not mint before the release time, and should mint after it
+#[test]
+fn schedule_mint_executed_after_release_time() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Anyone can call the function, but it is too early
+    testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(999).build());     // This is Charlie
+    let minted = contract.execute_scheduled_mints(10);
+    assert_eq!(0, minted.len(), "Nothing should be minted before the release time.");
+    assert!(contract.in_progress_nfts.get(&0).is_some(), "The draft should still exist.");
+
+    // Release time is reached
+    testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(1_000).build());
+    let minted = contract.execute_scheduled_mints(10);
+    assert_eq!(vec![0], minted, "The draft with ID 0 should be minted.");
+    assert!(contract.in_progress_nfts.get(&0).unwrap().minting, "The draft should be in minting state.");
+    assert!(!contract.scheduled_mints.iter().any(|((_, id), _)| id == 0), "The draft should be removed from the scheduled list.");
+}
+
+/// Draft that is not ready can not be scheduled
+#[test]
+#[should_panic(expected = "The draft is not ready to be minted!")]
+fn schedule_mint_not_ready_error() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_half_ready_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Release time has to be in the future
+#[test]
+#[should_panic(expected = "The scheduled time has to be in the future!")]
+fn schedule_mint_time_in_past_error() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp(5_000).build());  // Artist will be Alice
+    id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// If the Artist was removed from the master group, the scheduled mint is cancelled, but the draft is kept
+#[test]
+fn schedule_mint_cancelled_if_not_member() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Remove Alice from the master group
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    id = remove_member_from_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(1_000).build());  // This is Charlie
+    let minted = contract.execute_scheduled_mints(10);
+    assert_eq!(0, minted.len(), "Nothing should be minted.");
+    assert!(contract.in_progress_nfts.get(&0).unwrap().scheduled.is_none(), "The schedule should be cancelled.");
+    assert!(!contract.scheduled_mints.iter().any(|((_, id), _)| id == 0), "The draft should be removed from the scheduled list.");
+}
+
+/// Minting by hand will remove the draft from the scheduled list
+#[test]
+fn schedule_mint_manual_mint_removes_schedule() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+
+    // Create the master group
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Add Alice as member
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Prepare NFT
+    testing_env!(context.predecessor_account_id(accounts(0)).build());                          // Artist will be Alice
+    id = prepare_nft_full_proposal(&mut context, &mut contract);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Schedule the mint
+    id = schedule_mint_proposal(&mut context, &mut contract, 0, 1_000);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Mint by hand
+    id = mint_root_proposal(&mut context, &mut contract, 0);
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    assert!(!contract.scheduled_mints.iter().any(|((_, id), _)| id == 0), "The draft should be removed from the scheduled list.");
+}
+
+/// The schedule is ordered by the release time: a draft that is not due does not block the others, and rescheduling replaces the old time
+#[test]
+fn scheduled_mints_in_release_order() {
+    let mut context = VMContextBuilder::new();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());                          // This is Bob
+    let mut contract = Contract::new(
+        Config::test_config(),
+        VersionedPolicy::Default(vec![accounts(1).into()]),                                     // Council is Bob
+    );
+    let mut id = create_master_group_proposal(&mut context, &mut contract, "minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+    id = add_member_to_master_proposal(&mut context, &mut contract, accounts(0), "master_minting-contract-1.near".to_string());
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    // Alice schedules 3 drafts, draft 0 is released last
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    for (draft_id, release_time) in vec![(0, 5_000), (1, 1_000), (2, 2_000)] {
+        id = prepare_nft_full_proposal(&mut context, &mut contract);
+        contract.act_proposal(id, Action::VoteApprove, None);
+        id = schedule_mint_proposal(&mut context, &mut contract, draft_id, release_time);
+        contract.act_proposal(id, Action::VoteApprove, None);
+    }
+    id = schedule_mint_proposal(&mut context, &mut contract, 2, 1_500);                          // Draft 2 is moved earlier
+    contract.act_proposal(id, Action::VoteApprove, None);
+    assert_eq!(3, contract.get_scheduled_mints(0, 10).len(), "The old release time of draft 2 should be removed.");
+    assert_eq!(Some(1_500), contract.get_scheduled_mints(0, 10)[1].scheduled, "Draft 2 should be the second.");
+
+    testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(2_000).build());   // Charlie
+    assert_eq!(vec![1], contract.execute_scheduled_mints(1), "Only 1 draft should be minted.");
+    testing_env!(context.build());
+    assert_eq!(vec![2], contract.execute_scheduled_mints(10), "Draft 2 is due, draft 0 is not.");
+    testing_env!(context.build());
+    assert!(contract.execute_scheduled_mints(10).is_empty(), "Nothing else is due.");
+    assert_eq!(1, contract.get_scheduled_mints(0, 10).len(), "Draft 0 should still be scheduled.");
+}
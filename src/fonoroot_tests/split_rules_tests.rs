@@ -0,0 +1,164 @@
+use std::collections::{HashMap};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::json_types::{U128};
+use near_sdk::{testing_env};
+use near_sdk_sim::to_yocto;
+use crate::fonoroot_tests::helpers::{
+    setup_contract_with_minted_song,
+    set_split_rules_proposal,
+    alter_revenue_table_proposal
+};
+use crate::types::{Action, FixedTier, RevenueTable, SplitRules, ThresholdSplit};
+use crate::Contract;
+
+
+/// Alice 80%, Charlie 20%
+fn second_table() -> RevenueTable {
+    let mut table = HashMap::new();
+    table.insert(accounts(0), 8000);
+    table.insert(accounts(2), 2000);
+    RevenueTable::new(table, 15).unwrap()
+}
+
+/// Song 0 of Alice is minted (Alice 90%, Bob 10%, price is 5 NEAR), and the split rules are set to `rules`
+fn setup_contract_with_rules(context: &mut VMContextBuilder, rules: SplitRules) -> Contract {
+    let mut contract = setup_contract_with_minted_song(context);
+
+    let id = set_split_rules_proposal(context, &mut contract, 0, Some(rules));
+    contract.act_proposal(id, Action::VoteApprove, None);
+    contract
+}
+
+/// Charlie buys the song for 5 NEAR
+fn sell(contract: &mut Contract) {
+    contract.buy_nft_callback(Ok(true.into()), 0, accounts(2), U128(to_yocto("5")), 0);
+}
+
+/// The fixed tier is recouped first, the percentages apply to the rest
+#[test]
+fn fixed_tier_is_recouped_first() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("7")), max_share: None }],
+        thresholds: vec![],
+    });
+
+    sell(&mut contract);
+    assert_eq!(U128(to_yocto("5")), contract.get_claimable(accounts(3)), "Danny should get the whole first sale.");
+    assert_eq!(U128(0), contract.get_claimable(accounts(0)), "Alice should get nothing yet.");
+
+    sell(&mut contract);
+    assert_eq!(U128(to_yocto("7")), contract.get_claimable(accounts(3)), "Danny should get 7 NEAR in total.");
+    assert_eq!(U128(to_yocto("2.7")), contract.get_claimable(accounts(0)), "Alice should get 90% of the remaining 3 NEAR.");
+    assert_eq!(U128(to_yocto("0.3")), contract.get_claimable(accounts(1)), "Bob should get 10% of the remaining 3 NEAR.");
+
+    let income_table = contract.income_tables.get(&0).unwrap();
+    assert_eq!(Some(&to_yocto("7")), income_table.recouped.get(&accounts(3)), "7 NEAR should be recouped.");
+    assert_eq!(to_yocto("10"), income_table.revenue_split, "10 NEAR should be split in total.");
+}
+
+/// With a recoupment cap, the tier only gets a share of each payout
+#[test]
+fn recoupment_cap_limits_the_share() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("50")), max_share: Some(5000) }],
+        thresholds: vec![],
+    });
+
+    sell(&mut contract);
+    assert_eq!(U128(to_yocto("2.5")), contract.get_claimable(accounts(3)), "Danny should get 50% of the sale.");
+    assert_eq!(U128(to_yocto("2.25")), contract.get_claimable(accounts(0)), "Alice should get 90% of the other half.");
+    assert_eq!(U128(to_yocto("0.25")), contract.get_claimable(accounts(1)), "Bob should get 10% of the other half.");
+}
+
+/// The percentages change after the threshold, the payout that crosses the threshold is split in two parts
+#[test]
+fn threshold_changes_the_percentages() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![],
+        thresholds: vec![ThresholdSplit { threshold: U128(to_yocto("7.5")), revenue_table: second_table() }],
+    });
+
+    sell(&mut contract);
+    assert_eq!(U128(to_yocto("4.5")), contract.get_claimable(accounts(0)), "Below the threshold, the RevenueTable is used.");
+
+    sell(&mut contract);                                                                        // 2.5 NEAR with the RevenueTable, 2.5 NEAR with the second table
+    assert_eq!(U128(to_yocto("8.75")), contract.get_claimable(accounts(0)), "Alice should get 4.5 + 2.25 + 2 NEAR.");
+    assert_eq!(U128(to_yocto("0.75")), contract.get_claimable(accounts(1)), "Bob should get 0.5 + 0.25 NEAR.");
+    assert_eq!(U128(to_yocto("0.5")), contract.get_claimable(accounts(2)), "Charlie should get 20% of 2.5 NEAR.");
+}
+
+/// Removing the rules goes back to the RevenueTable
+#[test]
+fn remove_split_rules() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("7")), max_share: None }],
+        thresholds: vec![],
+    });
+
+    let id = set_split_rules_proposal(&mut context, &mut contract, 0, None);
+    contract.act_proposal(id, Action::VoteApprove, None);
+    sell(&mut contract);
+
+    assert_eq!(U128(0), contract.get_claimable(accounts(3)), "Danny should get nothing.");
+    assert_eq!(U128(to_yocto("4.5")), contract.get_claimable(accounts(0)), "Alice should get 90%.");
+}
+
+/// AlterRevenueTable keeps the split rules
+#[test]
+fn alter_revenue_table_keeps_split_rules() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("7")), max_share: None }],
+        thresholds: vec![],
+    });
+
+    let id = alter_revenue_table_proposal(&mut context, &mut contract, 0, (*second_table()).clone(), U128(to_yocto("5")));
+    contract.act_proposal(id, Action::VoteApprove, None);
+
+    let catalogue = contract.get_catalogue(accounts(0));
+    assert_eq!(1, catalogue[0].1.as_ref().unwrap().split_rules.as_ref().unwrap().tiers.len(), "The fixed tier should be kept.");
+}
+
+/// Only the owner can set the split rules
+#[test]
+#[should_panic(expected = "Only the owner (Artist) can alter the split rules!")]
+fn set_split_rules_not_owner_error() {
+    let mut context = VMContextBuilder::new();
+    let mut contract = setup_contract_with_minted_song(&mut context);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).signer_account_id(accounts(1)).build());     // Bob
+    let id = set_split_rules_proposal(&mut context, &mut contract, 0, None);
+    contract.act_proposal(id, Action::VoteApprove, None);
+}
+
+/// Thresholds have to be in increasing order
+#[test]
+#[should_panic(expected = "The thresholds have to be in increasing order, and more than 0!")]
+fn set_split_rules_wrong_order_error() {
+    let mut context = VMContextBuilder::new();
+    setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![],
+        thresholds: vec![
+            ThresholdSplit { threshold: U128(to_yocto("10")), revenue_table: second_table() },
+            ThresholdSplit { threshold: U128(to_yocto("5")), revenue_table: second_table() },
+        ],
+    });
+}
+
+/// An account can not have two fixed tiers
+#[test]
+#[should_panic(expected = "An account can only have one fixed tier!")]
+fn set_split_rules_duplicate_tier_error() {
+    let mut context = VMContextBuilder::new();
+    setup_contract_with_rules(&mut context, SplitRules {
+        tiers: vec![
+            FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("1")), max_share: None },
+            FixedTier { beneficiary: accounts(3), amount: U128(to_yocto("2")), max_share: None },
+        ],
+        thresholds: vec![],
+    });
+}
@@ -50,13 +50,13 @@ fn update_half_ready_test() {
     assert_eq!("Description of the updated NFT".to_string(), the_inserted_data.desc.unwrap(), "The Description is not correct!");
     assert_eq!(AccountId::new_unchecked("minting-contract-1.near".to_string()), the_inserted_data.contract, "The Minting Contract is not correctly set!");
     assert_eq!("QmerincKVRPTXh1z41725mFNvGp31UBgfyms5xWi1taNuQ".to_string(), the_inserted_data.image.unwrap(), "The Image CID is not correct!");
-    assert_eq!(Base64VecU8(vec![1,2,3]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
+    assert_eq!(Base64VecU8(vec![1; 32]), the_inserted_data.image_hash.unwrap(), "The Image hash is not correct!");
     assert_eq!("QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.music.unwrap(), "The Music Folder CID is not correct!");
-    assert_eq!(Base64VecU8(vec![4,5,6]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
+    assert_eq!(Base64VecU8(vec![4; 32]), the_inserted_data.music_hash.unwrap(), "The Music Folder hash is not correct!");
     assert_eq!("https://ipfs.io/ipfs/QmU51uX3B44Z4pH2XimaJ6eScRgAzG4XUrKfsz1yWVCo6f".to_string(), the_inserted_data.animation_url.unwrap(), "The Animation URL is not correct!");
-    assert_eq!(Base64VecU8(vec![7,8,9]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
+    assert_eq!(Base64VecU8(vec![7; 32]), the_inserted_data.animation_url_hash.unwrap(), "The Animation URL hash is not correct!");
     assert_eq!("QmxCBSWQcDwn3ktKdEfDn68bt2PQbx3khyqGQAeYAVabcd".to_string(), the_inserted_data.meta.unwrap(), "The Meta JSON CID is not correct!");
-    assert_eq!(Base64VecU8(vec![15,16,17]), the_inserted_data.meta_hash.unwrap(), "The Meta JSON hash is not correct!");
+    assert_eq!(Base64VecU8(vec![15; 32]), the_inserted_data.meta_hash.unwrap(), "The Meta JSON hash is not correct!");
 
     assert_eq!(0, the_inserted_data.id, "The ID of the newly inserted data should be 0.");
     assert_eq!(accounts(0), the_inserted_data.artist, "Artist (owner) should be Alice.");
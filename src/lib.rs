@@ -1,11 +1,12 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, TreeMap};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, TreeMap, Vector};
 use near_sdk::json_types::{Base58CryptoHash, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     log, env, ext_contract, near_bindgen, AccountId, Balance, BorshStorageKey, CryptoHash,
     PanicOnDefault, Promise, PromiseResult, Gas
 };
+use std::collections::HashMap;
 
 pub use crate::bounties::{Bounty, BountyClaim, VersionedBounty};
 pub use crate::policy::{
@@ -14,8 +15,9 @@ pub use crate::policy::{
 use crate::proposals::VersionedProposal;
 pub use crate::proposals::{Proposal, ProposalInput, ProposalKind, ProposalStatus};
 pub use crate::types::*;
+use crate::migration::{MigrationProgress, OldContract, STATE_VERSION};
 use crate::upgrade::{internal_get_factory_info, internal_set_factory_info, FactoryInfo};
-pub use crate::views::{BountyOutput, ProposalOutput};
+pub use crate::views::{BountyOutput, DraftOutput, ProposalOutput, SaleStatus};
 pub use crate::buy::*;
 //use crate::fonoroot_tests::*;
 
@@ -27,6 +29,12 @@ mod types;
 mod upgrade;
 pub mod views;
 pub mod buy;
+pub mod schedule;
+pub mod distribution;
+pub mod claim;
+pub mod migration;
+mod drafts;
+mod payee_groups;
 mod fonoroot_tests;
 
 #[derive(BorshStorageKey, BorshSerialize)]
@@ -44,7 +52,29 @@ pub enum StorageKeys {
     IncomeTables,
     UniqueIdToTreeIndex,
     FailedTransactions,
-    ArtistCatalogue(u64)
+    ArtistCatalogue(u64),
+    ScheduledMints,
+    FtWhitelist,
+    ClaimableRevenue,
+    DraftsByArtist,
+    DraftsByContract,
+    ArtistDrafts(AccountId),
+    ContractDrafts(AccountId),
+    ProposalFailures,
+    DraftStorageBalances,
+    PresaleAllowlists,
+    PresaleAllowlist(TreeIndex),
+    PurchaseStorage,
+    Sales,
+    SongSales(TreeIndex),
+    PurchasesByBuyer,
+    BuyerPurchases(AccountId),
+    PayeeGroups,
+    PayoutProgress,
+    PayoutQueue,
+    PayeeGroupParents,
+    OpenFailedTransactions,
+    BeneficiaryFailedTransactions(AccountId),
 }
 
 /// Function signatures of the callbacks that we have
@@ -53,12 +83,19 @@ pub trait ExtSelf {
     /// Callback after proposal execution.
     fn on_proposal_callback(&mut self, proposal_id: u64) -> PromiseOrValue<()>;
     /// Callback after FonoRoot minting contract created a new RootNFT. This callback will create a new empty IncomeTable, and a Catalogue for the Artist, if it does not exist yet.
-    fn mint_root_callback(&mut self, #[callback_result] result: Result<MintRootResult, near_sdk::PromiseError>, artist: AccountId);
+    /// It will remove the draft, or restore it if minting failed, and it will finalize the MintRoot proposal, if there was one.
+    fn mint_root_callback(&mut self, #[callback_result] result: Result<MintRootResult, near_sdk::PromiseError>, artist: AccountId, draft_id: u64, proposal_id: Option<u64>);
     /// Callback after FonoRoot minting contract moved the NFT to the buyer. This callback will update balances in IncomeTable
-    fn buy_nft_callback(&mut self, #[callback_result] result: Result<bool, near_sdk::PromiseError>, tree_index: TreeIndex);
+    fn buy_nft_callback(&mut self, #[callback_result] result: Result<VaultResult, near_sdk::PromiseError>, tree_index: TreeIndex, buyer: AccountId, price: U128, purchase_id: u64);
+    /// Same as buy_nft_callback, but for NFTs bought with a NEP-141 token. Returns the amount of tokens that should be refunded
+    fn ft_buy_nft_callback(&mut self, #[callback_result] result: Result<VaultResult, near_sdk::PromiseError>, tree_index: TreeIndex, buyer: AccountId, token_id: AccountId, amount: U128, price: U128, purchase_id: u64) -> U128;
+    /// Callback after the claimed revenue was sent. If the transfer failed, the amount is claimable again
+    fn claim_revenue_callback(&mut self, #[callback_result] result: Result<(), near_sdk::PromiseError>, account_id: AccountId, amount: U128);
+    /// Callback after a FailedTransaction was sent again. The FailedTransaction is Settled, or Pending again if the transfer failed
+    fn resend_failed_transaction_callback(&mut self, #[callback_result] result: Result<(), near_sdk::PromiseError>, failed_id: u64, receiver: AccountId);
 
     /// only a test
-    pub fn transfer_callback(&mut self, #[callback_result] result: Result<String, near_sdk::PromiseError>, beneficiary: AccountId, amount: U128);
+    pub fn transfer_callback(&mut self, #[callback_result] result: Result<String, near_sdk::PromiseError>, beneficiary: AccountId, amount: U128, token_id: Option<AccountId>);
 }
 
 #[near_bindgen]
@@ -100,6 +137,10 @@ pub struct Contract {
     pub in_progress_nfts: LookupMap<u64, InProgressMetadata>,
     /// Identifier for in_progress_nfts
     pub in_progress_nonce: u64,
+    /// IDs of the in_progress_nfts for each Artist
+    pub drafts_by_artist: LookupMap<AccountId, UnorderedSet<u64>>,
+    /// IDs of the in_progress_nfts for each minting contract
+    pub drafts_by_contract: LookupMap<AccountId, UnorderedSet<u64>>,
     /// List of Catalogue-s. Every Artist has a Catalogue.
     pub catalogues: LookupMap<AccountId, Catalogue>,
     /// Chronological list of NFTs, contains information on income and other things, for example price.
@@ -113,6 +154,64 @@ pub struct Contract {
     pub failed_transactions: UnorderedMap<u64, FailedTransaction>,
     /// Failed transaction nonce
     pub failed_nonce: u64,
+    /// IDs of the failed transactions that are still owed to the beneficiary (Pending or Retried), see `get_failed_transactions_for()`
+    pub open_failed_transactions: LookupMap<AccountId, UnorderedSet<u64>>,
+    /// InProgressNfts that are scheduled to be minted, ordered by the release time ((release time, draft ID) -> ()). See `execute_scheduled_mints()`
+    pub scheduled_mints: TreeMap<(u64, u64), ()>,
+    /// NEP-141 token contracts that can be used to buy NFTs
+    pub ft_whitelist: UnorderedSet<AccountId>,
+    /// Revenue in yoctoNEAR that the beneficiaries can withdraw with `claim_revenue()`
+    pub claimable_revenue: LookupMap<AccountId, Balance>,
+    /// Sum of all the claimable revenue
+    pub total_claimable: Balance,
+    /// Reason of the failure, for proposals that are in Failed state because of a FonoRoot action (for example MintRoot)
+    pub proposal_failures: LookupMap<u64, String>,
+    /// Prepaid storage deposit of the Artists, drafts are paid from this. See `draft_storage_deposit()`
+    pub draft_storage_balances: LookupMap<AccountId, Balance>,
+    /// Sum of the draft storage balances, the Artists can withdraw it any time
+    pub total_draft_storage_balance: Balance,
+    /// Accounts that can buy the song during the presale (TreeIndex -> accounts). See `PriceSchedule::Presale`
+    pub presale_allowlists: LookupMap<TreeIndex, UnorderedSet<AccountId>>,
+    /// Storage accounting of the purchases (purchase ID -> PurchaseStorage)
+    pub purchase_storage: UnorderedMap<u64, PurchaseStorage>,
+    /// ID of the next purchase
+    pub purchase_nonce: u64,
+    /// Sum of the storage costs of the purchases that were paid from the balance of the DAO
+    pub total_storage_spent_by_dao: Balance,
+    /// $NEAR deposits of the purchases that are waiting for the callback (price + VAULT_STORAGE_DEPOSIT), they are refunded if the purchase fails
+    pub pending_purchase_deposits: Balance,
+    /// Append-only sales log of the songs (TreeIndex -> sales)
+    pub sales: LookupMap<TreeIndex, Vector<Sale>>,
+    /// Purchases of the buyers, as (TreeIndex, index in the sales log of the song)
+    pub purchases_by_buyer: LookupMap<AccountId, Vector<(TreeIndex, u64)>>,
+    /// Named payee groups (group ID -> PayeeGroup). A RevenueTable can pay a group, that is split further. See `payee_groups.rs`
+    pub payee_groups: UnorderedMap<AccountId, PayeeGroup>,
+    /// Payee groups that contain the account directly (account -> groups). Only accounts in the namespace of the DAO are recorded
+    pub payee_group_parents: LookupMap<AccountId, Vec<AccountId>>,
+    /// TreeIndex where the next `distribute_due_revenue()` call starts
+    pub distribution_cursor: TreeIndex,
+    /// Progress and per-song results of the PayoutRevenue proposals (proposal ID -> progress)
+    pub payout_progress: LookupMap<u64, PayoutProgress>,
+    /// Transfers of the payouts that were not sent yet (queue position -> PayoutTransfer). See `process_payout_queue()`
+    pub payout_queue: LookupMap<u64, PayoutTransfer>,
+    /// Position of the first transfer in the payout queue
+    pub payout_queue_start: u64,
+    /// Position after the last transfer in the payout queue
+    pub payout_queue_end: u64,
+    /// Sum of the $NEAR transfers in the payout queue
+    pub queued_revenue: Balance,
+    /// Platform fees in the DAO treasury, that were not withdrawn yet. See `platform_fee` in Policy
+    pub platform_fee_balance: Balance,
+    /// Sum of every platform fee that was collected
+    pub total_platform_fees: Balance,
+    /// Platform fees of the NEP-141 sales that were not withdrawn yet (token contract -> amount)
+    pub ft_platform_fee_balances: HashMap<AccountId, Balance>,
+    /// Sum of every platform fee that was collected in NEP-141 tokens (token contract -> amount)
+    pub ft_total_platform_fees: HashMap<AccountId, Balance>,
+    /// Version of the stored state, see `migration.rs`
+    pub state_version: u32,
+    /// Progress of the migration, if some of the elements were not converted yet. See `migrate_state()`
+    pub migration: Option<MigrationProgress>,
 }
 
 #[near_bindgen]
@@ -135,12 +234,43 @@ impl Contract {
             locked_amount: 0,
             in_progress_nfts: LookupMap::new(StorageKeys::InProgressNfts),
             in_progress_nonce: 0,
+            drafts_by_artist: LookupMap::new(StorageKeys::DraftsByArtist),
+            drafts_by_contract: LookupMap::new(StorageKeys::DraftsByContract),
             catalogues: LookupMap::new(StorageKeys::Catalogues),
             income_tables: TreeMap::new(StorageKeys::IncomeTables),
             uniq_id_to_tree_index: UnorderedMap::new(StorageKeys::UniqueIdToTreeIndex),
             tree_index: 0,
             failed_transactions: UnorderedMap::new(StorageKeys::FailedTransactions),
-            failed_nonce: 0
+            failed_nonce: 0,
+            open_failed_transactions: LookupMap::new(StorageKeys::OpenFailedTransactions),
+            scheduled_mints: TreeMap::new(StorageKeys::ScheduledMints),
+            ft_whitelist: UnorderedSet::new(StorageKeys::FtWhitelist),
+            claimable_revenue: LookupMap::new(StorageKeys::ClaimableRevenue),
+            total_claimable: 0,
+            proposal_failures: LookupMap::new(StorageKeys::ProposalFailures),
+            draft_storage_balances: LookupMap::new(StorageKeys::DraftStorageBalances),
+            total_draft_storage_balance: 0,
+            presale_allowlists: LookupMap::new(StorageKeys::PresaleAllowlists),
+            purchase_storage: UnorderedMap::new(StorageKeys::PurchaseStorage),
+            purchase_nonce: 0,
+            total_storage_spent_by_dao: 0,
+            pending_purchase_deposits: 0,
+            sales: LookupMap::new(StorageKeys::Sales),
+            purchases_by_buyer: LookupMap::new(StorageKeys::PurchasesByBuyer),
+            payee_groups: UnorderedMap::new(StorageKeys::PayeeGroups),
+            payee_group_parents: LookupMap::new(StorageKeys::PayeeGroupParents),
+            distribution_cursor: 0,
+            payout_progress: LookupMap::new(StorageKeys::PayoutProgress),
+            payout_queue: LookupMap::new(StorageKeys::PayoutQueue),
+            payout_queue_start: 0,
+            payout_queue_end: 0,
+            queued_revenue: 0,
+            platform_fee_balance: 0,
+            total_platform_fees: 0,
+            ft_platform_fee_balances: HashMap::new(),
+            ft_total_platform_fees: HashMap::new(),
+            state_version: STATE_VERSION,
+            migration: None,
         };
         internal_set_factory_info(&FactoryInfo {
             factory_id: env::predecessor_account_id(),
@@ -150,14 +280,19 @@ impl Contract {
     }
 
     /// Should only be called by this contract on migration.
-    /// This is NOOP implementation. KEEP IT if you haven't changed contract state.
-    /// If you have changed state, you need to implement migration from old state (keep the old struct with different name to deserialize it first).
-    /// After migrate goes live on MainNet, return this implementation for next updates.
+    /// If the stored state is already the current version, this is NOOP.
+    /// Otherwise the old struct is converted (see `migration.rs`), and the elements of the collections have to be converted by `migrate_state()`.
     #[private]
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let this: Contract = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
-        this
+        let state = env::storage_read(b"STATE").expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
+        if let Ok(this) = Contract::try_from_slice(&state) {
+            return this;
+        }
+
+        let old: OldContract = OldContract::try_from_slice(&state).expect("Unknown state version, can not migrate!");
+        log!("Migrating state from version 1 to version {}", STATE_VERSION);
+        Contract::from_v1(old)
     }
 
     /// Remove blob from contract storage and pay back to original storer.
@@ -182,39 +317,53 @@ impl Contract {
     }
 
     /// Callback for MintRoot, this function will create the IncomeTable entry
+    /// If minting failed, the draft is restored, and the MintRoot proposal will be Failed. The Artist can retry with Action::Finalize on the proposal.
+    /// The callback does not panic, because a reverted callback would leave the draft in minting state forever. The gas of the callback is reserved by `internal_mint_root()`.
     #[private]
     pub fn mint_root_callback(
         &mut self, 
         #[callback_result] result: Result<MintRootResult, near_sdk::PromiseError>,
-        artist: AccountId
+        artist: AccountId,
+        draft_id: u64,
+        proposal_id: Option<u64>
     ) {
-        if result.is_err() {
-            panic!("MintRoot promise come back with an error.");
-        }
-
-        let mint_root_result: MintRootResult = result.unwrap();
-        let uniq_id = UniqId::new(mint_root_result.contract.clone(), mint_root_result.root_id.clone());
+        let mint_root_result: MintRootResult = match result {
+            Ok(mint_root_result) => mint_root_result,
+            Err(error) => {
+                log!("MintRoot promise come back with an error: {:?}. The draft {} is restored.", error, draft_id);
+                self.internal_fail_mint(draft_id, proposal_id, format!("The mint_root call failed: {:?}", error));
+                return;
+            }
+        };
+        let uniq_id = match self.internal_check_mint_root_result(&mint_root_result) {
+            Ok(uniq_id) => uniq_id,
+            Err(error) => {
+                log!("The result of mint_root can not be saved: {} The draft {} is restored.", error, draft_id);
+                self.internal_fail_mint(draft_id, proposal_id, error);
+                return;
+            }
+        };
         log!("MintRootCallback started! Uniq ID: {:?}", uniq_id);
 
-        assert_eq!(
-            self.income_tables.contains_key(&self.tree_index),
-            false,
-            "Duplicate TreeIndex error!"
-        );
-
-        assert_eq!(
-            self.uniq_id_to_tree_index.get(&uniq_id),
-            None,
-            "The UniqId already exists!"
-        );
-
         let new_income_table = IncomeTable {                                                // We create an new IncomeTable, balances are zero
             total_income: 0,                                                                // Price is not set. Price is set by CreateRevenueTable proposal
             current_balance: 0,
             root_id: mint_root_result.root_id,
             contract: mint_root_result.contract,
             owner: artist.clone(),
-            price: None
+            price: None,
+            ft_prices: HashMap::new(),
+            ft_total_income: HashMap::new(),
+            ft_balances: HashMap::new(),
+            royalty: 0,
+            sale_limits: SaleLimits::default(),
+            sold: 0,
+            pending_sales: 0,
+            price_schedule: None,
+            revenue_split: 0,
+            recouped: HashMap::new(),
+            auto_payout: None,
+            last_payout: env::block_timestamp(),
         };
 
         self.uniq_id_to_tree_index.insert(&uniq_id, &self.tree_index);
@@ -230,6 +379,11 @@ impl Contract {
         self.catalogues.insert(&artist, &catalogue_for_owner);                              // Insert back the catalogue to the artist
         self.tree_index = self.tree_index + 1;                                              // Increment TreeIndex
 
+        self.internal_remove_draft(draft_id);                                               // The NFT exists now, we don't need the draft anymore
+        if let Some(proposal_id) = proposal_id {
+            self.internal_finalize_mint_proposal(proposal_id, None);
+        }
+
         log!("MintRootCallback exiting, empty entry was inserted for the new song.");
     }    
 
@@ -239,20 +393,16 @@ impl Contract {
         &mut self, 
         #[callback_result] result: Result<(), near_sdk::PromiseError>,
         beneficiary: AccountId,
-        amount: U128
+        amount: U128,
+        token_id: Option<AccountId>
     ) {
         if result.is_err() {
-            log!("WARNING! This transfer failed: {} - {:?}", beneficiary, amount);
+            log!("WARNING! This transfer failed: {} - {:?} (token: {:?})", beneficiary, amount, token_id);
             
-            let failed_transaction_details = FailedTransaction {
-                beneficiary: beneficiary,
-                amount: u128::from(amount.clone())
-            };
-
-            self.failed_transactions.insert(&self.failed_nonce, &failed_transaction_details);
-            self.failed_nonce = self.failed_nonce + 1;
+            let failed_transaction_details = FailedTransaction::new(beneficiary, u128::from(amount.clone()), token_id);
+            self.internal_add_failed_transaction(failed_transaction_details);
         } else {
-            log!("Transfering {:?} yoctoNEAR to {} was successful!", amount, beneficiary);
+            log!("Transfering {:?} (token: {:?}) to {} was successful!", amount, token_id, beneficiary);
         }
     }
 }
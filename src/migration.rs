@@ -0,0 +1,540 @@
+//! Migration of the contract state between schema versions.
+//!
+//! `migrate()` only converts the `Contract` struct itself, the elements of the big collections (`income_tables`, the
+//! per-artist `Catalogue` maps, `failed_transactions`, the drafts and the proposals) are converted by `migrate_state()`,
+//! in batches, over several calls. New indexes (for example the draft indexes) are built by `migrate_state()` as well.
+//! Until the migration is finished, the functions that read these collections are blocked, see `assert_not_migrating()`.
+//!
+//! When a stored struct changes:
+//! 1. Increment `STATE_VERSION`.
+//! 2. Copy the current definition of the struct here, with an `Old` prefix, and implement `From<OldX> for X`.
+//! 3. If `Contract` changed, `OldContract` has to be the previous layout of `Contract`, and `migrate()` has to convert it.
+
+use near_sdk::IntoStorageKey;
+
+use near_sdk::json_types::Base64VecU8;
+
+use crate::proposals::{ActionCall, PolicyParameters, Vote, VersionedProposal};
+use crate::*;
+
+/// Current version of the stored state. Version 1 is the state before versioning was introduced.
+pub const STATE_VERSION: u32 = 2;
+
+/// Progress of an unfinished migration. The elements below the cursors are already converted.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MigrationProgress {
+    /// Version of the state that we are migrating from
+    pub from_version: u32,
+    /// Next TreeIndex to convert (IncomeTable and the CatalogueEntry of the owner)
+    pub next_tree_index: TreeIndex,
+    /// Next FailedTransaction ID to convert
+    pub next_failed_nonce: u64,
+    /// Next InProgressNft ID to convert, and add to the per-artist and per-contract indexes
+    pub next_draft_id: u64,
+    /// Next proposal ID to convert
+    pub next_proposal_id: u64,
+}
+
+/// Layout of `IncomeTable` in version 1
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldIncomeTable {
+    pub total_income: Balance,
+    pub current_balance: Balance,
+    pub root_id: TokenId,
+    pub contract: AccountId,
+    pub owner: AccountId,
+    pub price: Option<SalePriceInYoctoNear>,
+}
+
+impl From<OldIncomeTable> for IncomeTable {
+    fn from(old: OldIncomeTable) -> Self {
+        IncomeTable {
+            total_income: old.total_income,
+            current_balance: old.current_balance,
+            root_id: old.root_id,
+            contract: old.contract,
+            owner: old.owner,
+            price: old.price,
+            ft_prices: HashMap::new(),
+            ft_total_income: HashMap::new(),
+            ft_balances: HashMap::new(),
+            royalty: 0,
+            sale_limits: SaleLimits::default(),
+            sold: 0,
+            pending_sales: 0,
+            price_schedule: None,
+            revenue_split: old.total_income.saturating_sub(old.current_balance),    // Everything that is not in the balance was paid out
+            recouped: HashMap::new(),
+            auto_payout: None,
+            last_payout: 0,
+        }
+    }
+}
+
+/// Layout of `FailedTransaction` in version 1, it was only used for $NEAR
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldFailedTransaction {
+    pub beneficiary: AccountId,
+    pub amount: Balance,
+}
+
+impl From<OldFailedTransaction> for FailedTransaction {
+    fn from(old: OldFailedTransaction) -> Self {
+        FailedTransaction {
+            beneficiary: old.beneficiary,
+            amount: old.amount,
+            token_id: None,
+            status: FailedTransactionStatus::Pending,
+            history: Vec::new(),                                                    // Version 1 did not save when the transfer failed
+        }
+    }
+}
+
+/// Layout of `InProgressMetadata` in version 1, it did not have the minting state
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldInProgressMetadata {
+    pub id: u64,
+    pub initiated: u64,
+    pub artist: AccountId,
+    pub contract: AccountId,
+    pub scheduled: Option<u64>,
+    pub title: Option<String>,
+    pub desc: Option<String>,
+    pub meta: Option<String>,
+    pub meta_hash: Option<Base64VecU8>,
+    pub image: Option<String>,
+    pub image_hash: Option<Base64VecU8>,
+    pub music: Option<String>,
+    pub music_hash: Option<Base64VecU8>,
+    pub animation_url: Option<String>,
+    pub animation_url_hash: Option<Base64VecU8>,
+}
+
+impl From<OldInProgressMetadata> for InProgressMetadata {
+    fn from(old: OldInProgressMetadata) -> Self {
+        InProgressMetadata {
+            id: old.id,
+            initiated: old.initiated,
+            artist: old.artist,
+            contract: old.contract,
+            scheduled: None,                                                                // ScheduleMint was not implemented in version 1
+            minting: false,
+            storage_cost: 0,
+            title: old.title,
+            desc: old.desc,
+            meta: old.meta,
+            meta_hash: old.meta_hash,
+            image: old.image,
+            image_hash: old.image_hash,
+            music: old.music,
+            music_hash: old.music_hash,
+            animation_url: old.animation_url,
+            animation_url_hash: old.animation_url_hash,
+        }
+    }
+}
+
+/// Layout of `CatalogueEntry` in version 1, it did not have split rules
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldCatalogueEntry {
+    pub revenue_table: RevenueTable,
+}
+
+impl From<OldCatalogueEntry> for CatalogueEntry {
+    fn from(old: OldCatalogueEntry) -> Self {
+        CatalogueEntry {
+            revenue_table: old.revenue_table,
+            split_rules: None,
+        }
+    }
+}
+
+/// Per-artist Catalogue, with the old entry type
+pub type OldCatalogue = UnorderedMap<TreeIndex, Option<OldCatalogueEntry>>;
+
+/// Layout of `Policy` in version 1, it did not have the payout limits
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldPolicy {
+    pub roles: Vec<RolePermission>,
+    pub default_vote_policy: VotePolicy,
+    pub proposal_bond: U128,
+    pub proposal_period: U64,
+    pub bounty_bond: U128,
+    pub bounty_forgiveness_period: U64,
+}
+
+/// Layout of `VersionedPolicy` in version 1
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum OldVersionedPolicy {
+    Default(Vec<AccountId>),
+    Current(OldPolicy),
+}
+
+impl From<OldVersionedPolicy> for VersionedPolicy {
+    fn from(old: OldVersionedPolicy) -> Self {
+        match old {
+            OldVersionedPolicy::Default(accounts) => VersionedPolicy::Default(accounts),
+            OldVersionedPolicy::Current(old) => {
+                let defaults = default_policy(vec![]);
+                VersionedPolicy::Current(Policy {
+                    roles: old.roles,
+                    default_vote_policy: old.default_vote_policy,
+                    proposal_bond: old.proposal_bond,
+                    proposal_period: old.proposal_period,
+                    bounty_bond: old.bounty_bond,
+                    bounty_forgiveness_period: old.bounty_forgiveness_period,
+                    max_revenue_table_length: defaults.max_revenue_table_length,        // The limits that were hardcoded in version 1
+                    max_payout_transfers: defaults.max_payout_transfers,
+                    platform_fee: 0,
+                })
+            }
+        }
+    }
+}
+
+/// Layout of `PolicyParameters` in version 1, it did not have the payout limits and the platform fee
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldPolicyParameters {
+    pub proposal_bond: Option<U128>,
+    pub proposal_period: Option<U64>,
+    pub bounty_bond: Option<U128>,
+    pub bounty_forgiveness_period: Option<U64>,
+}
+
+impl From<OldPolicyParameters> for PolicyParameters {
+    fn from(old: OldPolicyParameters) -> Self {
+        PolicyParameters {
+            proposal_bond: old.proposal_bond,
+            proposal_period: old.proposal_period,
+            bounty_bond: old.bounty_bond,
+            bounty_forgiveness_period: old.bounty_forgiveness_period,
+            max_revenue_table_length: None,
+            max_payout_transfers: None,
+            platform_fee: None,
+        }
+    }
+}
+
+/// Layout of `ProposalKind` in version 1. The variants have to stay in the same order
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum OldProposalKind {
+    ChangeConfig { config: Config },
+    ChangePolicy { policy: OldVersionedPolicy },
+    AddMemberToRole { member_id: AccountId, role: String },
+    RemoveMemberFromRole { member_id: AccountId, role: String },
+    FunctionCall { receiver_id: AccountId, actions: Vec<ActionCall> },
+    UpgradeSelf { hash: Base58CryptoHash },
+    UpgradeRemote { receiver_id: AccountId, method_name: String, hash: Base58CryptoHash },
+    Transfer { token_id: OldAccountId, receiver_id: AccountId, amount: U128, msg: Option<String> },
+    SetStakingContract { staking_id: AccountId },
+    AddBounty { bounty: Bounty },
+    BountyDone { bounty_id: u64, receiver_id: AccountId },
+    Vote,
+    FactoryInfoUpdate { factory_info: FactoryInfo },
+    ChangePolicyAddOrUpdateRole { role: RolePermission },
+    ChangePolicyRemoveRole { role: String },
+    ChangePolicyUpdateDefaultVotePolicy { vote_policy: VotePolicy },
+    ChangePolicyUpdateParameters { parameters: OldPolicyParameters },
+    MintRoot { id: u64 },
+    PrepareNft { nft_data: NftDataFromFrontEnd },
+    UpdatePrepairedNft { id: u64, new_nft_data: NftDataFromFrontEnd },
+    CreateRevenueTable { root_id: TokenId, contract: AccountId, unsafe_table: HashMap<AccountId, u64>, price: SalePriceInYoctoNear },
+    AlterRevenueTable { tree_index: TreeIndex, unsafe_table: HashMap<AccountId, u64>, price: SalePriceInYoctoNear },
+    PayoutRevenue { tree_index_list: Vec<TreeIndex> },
+    ResendFailedTransaction { failed_id: u64, new_address: AccountId },
+    ScheduleMint { params: String },                                                // It was a placeholder, see below
+}
+
+impl From<OldProposalKind> for ProposalKind {
+    fn from(old: OldProposalKind) -> Self {
+        match old {
+            OldProposalKind::ChangeConfig { config } => ProposalKind::ChangeConfig { config },
+            OldProposalKind::ChangePolicy { policy } => ProposalKind::ChangePolicy { policy: policy.into() },
+            OldProposalKind::AddMemberToRole { member_id, role } => ProposalKind::AddMemberToRole { member_id, role },
+            OldProposalKind::RemoveMemberFromRole { member_id, role } => ProposalKind::RemoveMemberFromRole { member_id, role },
+            OldProposalKind::FunctionCall { receiver_id, actions } => ProposalKind::FunctionCall { receiver_id, actions },
+            OldProposalKind::UpgradeSelf { hash } => ProposalKind::UpgradeSelf { hash },
+            OldProposalKind::UpgradeRemote { receiver_id, method_name, hash } => ProposalKind::UpgradeRemote { receiver_id, method_name, hash },
+            OldProposalKind::Transfer { token_id, receiver_id, amount, msg } => ProposalKind::Transfer { token_id, receiver_id, amount, msg },
+            OldProposalKind::SetStakingContract { staking_id } => ProposalKind::SetStakingContract { staking_id },
+            OldProposalKind::AddBounty { bounty } => ProposalKind::AddBounty { bounty },
+            OldProposalKind::BountyDone { bounty_id, receiver_id } => ProposalKind::BountyDone { bounty_id, receiver_id },
+            OldProposalKind::Vote => ProposalKind::Vote,
+            OldProposalKind::FactoryInfoUpdate { factory_info } => ProposalKind::FactoryInfoUpdate { factory_info },
+            OldProposalKind::ChangePolicyAddOrUpdateRole { role } => ProposalKind::ChangePolicyAddOrUpdateRole { role },
+            OldProposalKind::ChangePolicyRemoveRole { role } => ProposalKind::ChangePolicyRemoveRole { role },
+            OldProposalKind::ChangePolicyUpdateDefaultVotePolicy { vote_policy } => ProposalKind::ChangePolicyUpdateDefaultVotePolicy { vote_policy },
+            OldProposalKind::ChangePolicyUpdateParameters { parameters } => ProposalKind::ChangePolicyUpdateParameters { parameters: parameters.into() },
+            OldProposalKind::MintRoot { id } => ProposalKind::MintRoot { id },
+            OldProposalKind::PrepareNft { nft_data } => ProposalKind::PrepareNft { nft_data },
+            OldProposalKind::UpdatePrepairedNft { id, new_nft_data } => ProposalKind::UpdatePrepairedNft { id, new_nft_data },
+            OldProposalKind::CreateRevenueTable { root_id, contract, unsafe_table, price } => ProposalKind::CreateRevenueTable { root_id, contract, unsafe_table, price },
+            OldProposalKind::AlterRevenueTable { tree_index, unsafe_table, price } => ProposalKind::AlterRevenueTable { tree_index, unsafe_table, price },
+            OldProposalKind::PayoutRevenue { tree_index_list } => ProposalKind::PayoutRevenue { tree_index_list },
+            OldProposalKind::ResendFailedTransaction { failed_id, new_address } => ProposalKind::ResendFailedTransaction { failed_id, new_address },
+            // ScheduleMint did not have real parameters in version 1, and executing it did nothing. The params can't be converted
+            // to a draft ID and a time, so it becomes a signaling Vote, that does nothing either.
+            OldProposalKind::ScheduleMint { .. } => ProposalKind::Vote,
+        }
+    }
+}
+
+/// Layout of `Proposal` in version 1, only the kind is different
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldProposal {
+    pub proposer: AccountId,
+    pub description: String,
+    pub kind: OldProposalKind,
+    pub status: ProposalStatus,
+    pub vote_counts: HashMap<String, [Balance; 3]>,
+    pub votes: HashMap<AccountId, Vote>,
+    pub submission_time: U64,
+}
+
+/// Layout of `VersionedProposal` in version 1
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum OldVersionedProposal {
+    Default(OldProposal),
+}
+
+impl From<OldVersionedProposal> for VersionedProposal {
+    fn from(old: OldVersionedProposal) -> Self {
+        match old {
+            OldVersionedProposal::Default(old) => VersionedProposal::Default(Proposal {
+                proposer: old.proposer,
+                description: old.description,
+                kind: old.kind.into(),
+                status: old.status,
+                vote_counts: old.vote_counts,
+                votes: old.votes,
+                submission_time: old.submission_time,
+            }),
+        }
+    }
+}
+
+/// Layout of `Contract` in version 1
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldContract {
+    pub config: LazyOption<Config>,
+    pub policy: LazyOption<OldVersionedPolicy>,
+    pub locked_amount: Balance,
+    pub staking_id: Option<AccountId>,
+    pub total_delegation_amount: Balance,
+    pub delegations: LookupMap<AccountId, Balance>,
+    pub last_proposal_id: u64,
+    pub proposals: LookupMap<u64, OldVersionedProposal>,
+    pub last_bounty_id: u64,
+    pub bounties: LookupMap<u64, VersionedBounty>,
+    pub bounty_claimers: LookupMap<AccountId, Vec<BountyClaim>>,
+    pub bounty_claims_count: LookupMap<u64, u32>,
+    pub blobs: LookupMap<CryptoHash, AccountId>,
+    pub in_progress_nfts: LookupMap<u64, OldInProgressMetadata>,
+    pub in_progress_nonce: u64,
+    pub catalogues: LookupMap<AccountId, OldCatalogue>,
+    pub income_tables:  TreeMap<TreeIndex, OldIncomeTable>,
+    pub uniq_id_to_tree_index: UnorderedMap<UniqId, TreeIndex>,
+    pub tree_index: TreeIndex,
+    pub failed_transactions: UnorderedMap<u64, OldFailedTransaction>,
+    pub failed_nonce: u64,
+}
+
+/// Collections only store their prefix (and length) in the state, the element type is not stored,
+/// so the same collection can be read with the old element type.
+fn retype<A: BorshSerialize, B: BorshDeserialize>(collection: &A) -> B {
+    B::try_from_slice(&collection.try_to_vec().unwrap()).expect("Collection layout mismatch!")
+}
+
+/// The values of a TreeMap are stored in a LookupMap with the prefix of the TreeMap + 'v'.
+/// We need this, because `TreeMap::insert()` would deserialize the old value with the new type.
+fn income_table_values<V: BorshSerialize + BorshDeserialize>() -> LookupMap<TreeIndex, V> {
+    let mut prefix = StorageKeys::IncomeTables.into_storage_key();
+    prefix.push(b'v');
+    LookupMap::new(prefix)
+}
+
+impl Contract {
+    /// Converts the state from version 1. The elements of the collections are converted later, by `migrate_state()`.
+    pub(crate) fn from_v1(old: OldContract) -> Self {
+        let migration = if old.tree_index > 0 || old.failed_nonce > 0 || old.in_progress_nonce > 0 || old.last_proposal_id > 0 {
+            Some(MigrationProgress { from_version: 1, next_tree_index: 0, next_failed_nonce: 0, next_draft_id: 0, next_proposal_id: 0 })
+        } else {
+            None
+        };
+
+        Self {
+            config: old.config,
+            policy: LazyOption::new(StorageKeys::Policy, old.policy.get().map(VersionedPolicy::from).as_ref()),   // Small, converted right away
+            locked_amount: old.locked_amount,
+            staking_id: old.staking_id,
+            total_delegation_amount: old.total_delegation_amount,
+            delegations: old.delegations,
+            last_proposal_id: old.last_proposal_id,
+            proposals: retype(&old.proposals),
+            last_bounty_id: old.last_bounty_id,
+            bounties: old.bounties,
+            bounty_claimers: old.bounty_claimers,
+            bounty_claims_count: old.bounty_claims_count,
+            blobs: old.blobs,
+            in_progress_nfts: retype(&old.in_progress_nfts),
+            in_progress_nonce: old.in_progress_nonce,
+            drafts_by_artist: LookupMap::new(StorageKeys::DraftsByArtist),
+            drafts_by_contract: LookupMap::new(StorageKeys::DraftsByContract),
+            catalogues: retype(&old.catalogues),
+            income_tables: retype(&old.income_tables),
+            uniq_id_to_tree_index: old.uniq_id_to_tree_index,
+            tree_index: old.tree_index,
+            failed_transactions: retype(&old.failed_transactions),
+            failed_nonce: old.failed_nonce,
+            open_failed_transactions: LookupMap::new(StorageKeys::OpenFailedTransactions),
+            scheduled_mints: TreeMap::new(StorageKeys::ScheduledMints),
+            ft_whitelist: UnorderedSet::new(StorageKeys::FtWhitelist),
+            claimable_revenue: LookupMap::new(StorageKeys::ClaimableRevenue),
+            total_claimable: 0,
+            proposal_failures: LookupMap::new(StorageKeys::ProposalFailures),
+            draft_storage_balances: LookupMap::new(StorageKeys::DraftStorageBalances),
+            total_draft_storage_balance: 0,
+            presale_allowlists: LookupMap::new(StorageKeys::PresaleAllowlists),
+            purchase_storage: UnorderedMap::new(StorageKeys::PurchaseStorage),
+            purchase_nonce: 0,
+            total_storage_spent_by_dao: 0,
+            pending_purchase_deposits: 0,
+            sales: LookupMap::new(StorageKeys::Sales),
+            purchases_by_buyer: LookupMap::new(StorageKeys::PurchasesByBuyer),
+            payee_groups: UnorderedMap::new(StorageKeys::PayeeGroups),
+            payee_group_parents: LookupMap::new(StorageKeys::PayeeGroupParents),
+            distribution_cursor: 0,
+            payout_progress: LookupMap::new(StorageKeys::PayoutProgress),
+            payout_queue: LookupMap::new(StorageKeys::PayoutQueue),
+            payout_queue_start: 0,
+            payout_queue_end: 0,
+            queued_revenue: 0,
+            platform_fee_balance: 0,
+            total_platform_fees: 0,
+            ft_platform_fee_balances: HashMap::new(),
+            ft_total_platform_fees: HashMap::new(),
+            state_version: STATE_VERSION,
+            migration: migration,
+        }
+    }
+
+    /// Panics while `migrate_state()` did not convert every element, so the functions and the views can't read half-migrated songs,
+    /// failed transactions, drafts or proposals
+    pub(crate) fn assert_not_migrating(&self) {
+        assert!(self.migration.is_none(), "The state migration is not finished yet, call migrate_state()!");
+    }
+
+    /// Converts the IncomeTable with `tree_index`, and the CatalogueEntry for it in the Catalogue of the owner
+    fn migrate_song_v1(&mut self, tree_index: TreeIndex) {
+        let mut old_values = income_table_values::<OldIncomeTable>();
+        let old_table = match old_values.get(&tree_index) {
+            Some(old_table) => old_table,
+            None => return,
+        };
+        let owner = old_table.owner.clone();
+        let new_table: IncomeTable = old_table.into();
+        old_values.insert_raw(&tree_index.try_to_vec().unwrap(), &new_table.try_to_vec().unwrap());
+
+        if let Some(catalogue) = self.catalogues.get(&owner) {
+            let mut old_catalogue: OldCatalogue = retype(&catalogue);
+            if let Some(old_entry) = old_catalogue.get(&tree_index) {
+                let new_entry: Option<CatalogueEntry> = old_entry.map(|entry| entry.into());
+                old_catalogue.insert_raw(&tree_index.try_to_vec().unwrap(), &new_entry.try_to_vec().unwrap());
+            }
+        }
+    }
+
+    /// Converts the InProgressNft with `id`, and adds it to the indexes, that did not exist in version 1
+    fn migrate_draft_v1(&mut self, id: u64) {
+        let mut old_drafts: LookupMap<u64, OldInProgressMetadata> = retype(&self.in_progress_nfts);
+        if let Some(old_draft) = old_drafts.get(&id) {
+            let new_draft: InProgressMetadata = old_draft.into();
+            old_drafts.insert_raw(&id.try_to_vec().unwrap(), &new_draft.try_to_vec().unwrap());
+            self.internal_insert_draft(&new_draft);
+        }
+    }
+
+    /// Converts the proposal with `id`. Removed proposals are not in the map anymore
+    fn migrate_proposal_v1(&mut self, id: u64) {
+        let mut old_proposals: LookupMap<u64, OldVersionedProposal> = retype(&self.proposals);
+        if let Some(old_proposal) = old_proposals.get(&id) {
+            let new_proposal: VersionedProposal = old_proposal.into();
+            old_proposals.insert_raw(&id.try_to_vec().unwrap(), &new_proposal.try_to_vec().unwrap());
+        }
+    }
+
+    /// Converts the FailedTransaction with `id`, and adds it to the open failed transactions of the beneficiary
+    fn migrate_failed_transaction_v1(&mut self, id: u64) {
+        let mut old_transactions: UnorderedMap<u64, OldFailedTransaction> = retype(&self.failed_transactions);
+        if let Some(old_transaction) = old_transactions.get(&id) {
+            let new_transaction: FailedTransaction = old_transaction.into();
+            old_transactions.insert_raw(&id.try_to_vec().unwrap(), &new_transaction.try_to_vec().unwrap());
+            if new_transaction.is_open() {
+                Self::add_to_index(
+                    &mut self.open_failed_transactions,
+                    &new_transaction.beneficiary,
+                    id,
+                    StorageKeys::BeneficiaryFailedTransactions(new_transaction.beneficiary.clone())
+                );
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Converts at most `limit` elements of the state, that were not converted yet by `migrate()`.
+    /// Anyone can call this function, it can only move the migration forward. Call it until it returns None.
+    /// Returns the progress of the migration, or None if the migration is finished.
+    pub fn migrate_state(&mut self, limit: u64) -> Option<MigrationProgress> {
+        let mut progress = self.migration.clone().expect("There is no migration in progress!");
+        assert_eq!(progress.from_version, 1, "Unknown state version!");
+        let mut budget = limit;
+
+        while budget > 0 && progress.next_tree_index < self.tree_index {
+            self.migrate_song_v1(progress.next_tree_index);
+            progress.next_tree_index = progress.next_tree_index + 1;
+            budget = budget - 1;
+        }
+
+        while budget > 0 && progress.next_failed_nonce < self.failed_nonce {
+            self.migrate_failed_transaction_v1(progress.next_failed_nonce);
+            progress.next_failed_nonce = progress.next_failed_nonce + 1;
+            budget = budget - 1;
+        }
+
+        while budget > 0 && progress.next_draft_id < self.in_progress_nonce {
+            self.migrate_draft_v1(progress.next_draft_id);
+            progress.next_draft_id = progress.next_draft_id + 1;
+            budget = budget - 1;
+        }
+
+        while budget > 0 && progress.next_proposal_id < self.last_proposal_id {
+            self.migrate_proposal_v1(progress.next_proposal_id);
+            progress.next_proposal_id = progress.next_proposal_id + 1;
+            budget = budget - 1;
+        }
+
+        if progress.next_tree_index >= self.tree_index && progress.next_failed_nonce >= self.failed_nonce
+            && progress.next_draft_id >= self.in_progress_nonce && progress.next_proposal_id >= self.last_proposal_id {
+            log!("Migration from version {} is finished.", progress.from_version);
+            self.migration = None;
+        } else {
+            log!("Migration progress: {:?}", progress);
+            self.migration = Some(progress);
+        }
+
+        self.migration.clone()
+    }
+
+    /// Version of the stored state, see `STATE_VERSION`
+    pub fn get_state_version(&self) -> u32 {
+        self.state_version
+    }
+
+    /// Progress of the migration, None if there is no migration in progress
+    pub fn get_migration_progress(&self) -> Option<MigrationProgress> {
+        self.migration.clone()
+    }
+}
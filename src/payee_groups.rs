@@ -0,0 +1,110 @@
+use crate::*;
+
+impl Contract {
+    /// Replaces the payee groups in `parts` with their members, recursively. Amounts of the same account are added together.
+    /// Panics if a group contains itself, or if the groups are nested deeper than MAX_PAYEE_GROUP_DEPTH.
+    pub(crate) fn internal_expand_payee_groups(&self, parts: HashMap<AccountId, Balance>) -> HashMap<AccountId, Balance> {
+        self.internal_try_expand_payee_groups(parts).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `internal_expand_payee_groups()`, but returns the error instead of panicking. Used in the callbacks and in the payout cranks.
+    pub(crate) fn internal_try_expand_payee_groups(&self, parts: HashMap<AccountId, Balance>) -> Result<HashMap<AccountId, Balance>, String> {
+        let mut expanded: HashMap<AccountId, Balance> = HashMap::new();
+        for (beneficiary, amount) in parts {
+            self.internal_expand_payee(beneficiary, amount, &mut Vec::new(), &mut expanded)?;
+        }
+        Ok(expanded)
+    }
+
+    /// Adds `amount` for `beneficiary` to `expanded`. If `beneficiary` is a payee group, it is split between the members.
+    /// `path` is the list of groups that we are inside of, it is used for the cycle detection.
+    fn internal_expand_payee(&self, beneficiary: AccountId, amount: Balance, path: &mut Vec<AccountId>, expanded: &mut HashMap<AccountId, Balance>) -> Result<(), String> {
+        let group = match self.payee_groups.get(&beneficiary) {
+            Some(group) => group,
+            None => {
+                *expanded.entry(beneficiary).or_insert(0) += amount;
+                return Ok(());
+            }
+        };
+        if path.contains(&beneficiary) {
+            return Err(format!("The payee group {} contains itself!", beneficiary));
+        }
+        if path.len() >= MAX_PAYEE_GROUP_DEPTH {
+            return Err(format!("Payee groups can not be nested deeper than {} levels!", MAX_PAYEE_GROUP_DEPTH));
+        }
+
+        path.push(beneficiary);
+        for (member, part) in group.revenue_table.split(amount) {                           // The rounding remainder stays inside the group
+            self.internal_expand_payee(member, part, path, expanded)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    /// Groups that contain `group_id`, directly or through other groups
+    fn internal_payee_group_ancestors(&self, group_id: &AccountId) -> Vec<AccountId> {
+        let mut ancestors: Vec<AccountId> = Vec::new();
+        let mut to_visit: Vec<AccountId> = self.payee_group_parents.get(group_id).unwrap_or_default();
+        while let Some(parent) = to_visit.pop() {
+            if ancestors.contains(&parent) {
+                continue;                                                                   // Cycles are reported by the expansion
+            }
+            to_visit.extend(self.payee_group_parents.get(&parent).unwrap_or_default());
+            ancestors.push(parent);
+        }
+        ancestors
+    }
+
+    /// Records that `group_id` contains `member` (or removes it, if `add` is false). Only members inside the namespace of the DAO
+    /// are recorded, because only those can be payee groups, now or later.
+    fn internal_update_payee_group_parent(&mut self, member: &AccountId, group_id: &AccountId, add: bool) {
+        if !member.as_str().ends_with(&format!(".{}", env::current_account_id())) {
+            return;
+        }
+        let mut parents = self.payee_group_parents.get(member).unwrap_or_default();
+        parents.retain(|parent| parent != group_id);
+        if add {
+            parents.push(group_id.clone());
+        }
+        if parents.is_empty() {
+            self.payee_group_parents.remove(member);
+        } else {
+            self.payee_group_parents.insert(member, &parents);
+        }
+    }
+
+    /// Creates or updates a payee group. The ID of the group has to be a sub-account of the DAO, so it can't be the same as a real account
+    pub(crate) fn internal_set_payee_group(&mut self, group_id: &AccountId, revenue_table: RevenueTable) {
+        assert!(
+            group_id.as_str().ends_with(&format!(".{}", env::current_account_id())),
+            "The ID of a payee group has to be a sub-account of the DAO, like band.{}!",
+            env::current_account_id()
+        );
+        let owner = match self.payee_groups.get(group_id) {
+            Some(group) => {
+                assert_eq!(group.owner, env::signer_account_id(), "Only the owner can alter the payee group!");
+                for member in group.revenue_table.keys() {
+                    self.internal_update_payee_group_parent(member, group_id, false);
+                }
+                group.owner
+            },
+            None => env::signer_account_id(),                                              // The creator of the group is the owner
+        };
+
+        for member in revenue_table.keys() {
+            self.internal_update_payee_group_parent(member, group_id, true);
+        }
+        self.payee_groups.insert(group_id, &PayeeGroup {
+            owner: owner,
+            revenue_table: revenue_table,
+        });
+
+        // The change can make the groups that contain this group too deep, so they are checked as well. A RevenueTable (or a threshold table)
+        // expands its groups the same way as the group itself is expanded, so checking the groups covers every table that pays them.
+        let mut to_check = self.internal_payee_group_ancestors(group_id);
+        to_check.push(group_id.clone());
+        for group in to_check {
+            self.internal_expand_payee_groups(vec![(group, 10_000)].into_iter().collect());  // Panics on cycles and on too deep nesting
+        }
+    }
+}
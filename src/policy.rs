@@ -7,7 +7,7 @@ use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, AccountId, Balance};
 
 use crate::proposals::{PolicyParameters, Proposal, ProposalKind, ProposalStatus, Vote};
-use crate::types::Action;
+use crate::types::{Action, MAX_PLATFORM_FEE};
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
@@ -159,6 +159,23 @@ pub struct Policy {
     pub bounty_bond: U128,
     /// Period in which giving up on bounty is not punished.
     pub bounty_forgiveness_period: U64,
+    /// Maximum number of beneficiaries in a RevenueTable (and in a payee group).
+    #[serde(default = "default_max_revenue_table_length")]
+    pub max_revenue_table_length: u32,
+    /// Maximum number of queued payout transfers that are sent by one call. Gas can stop the sending earlier, see `distribution.rs`.
+    #[serde(default = "default_max_payout_transfers")]
+    pub max_payout_transfers: u32,
+    /// Fee of the DAO on every sale ($NEAR and NEP-141), in basis points. It is deducted in the buy callbacks, see `platform_fee_balance` and `ft_platform_fee_balances`.
+    #[serde(default)]
+    pub platform_fee: u32,
+}
+
+fn default_max_revenue_table_length() -> u32 {
+    15
+}
+
+fn default_max_payout_transfers() -> u32 {
+    15
 }
 
 /// Versioned policy.
@@ -205,6 +222,9 @@ pub fn default_policy(council: Vec<AccountId>) -> Policy {
         proposal_period: U64::from(1_000_000_000 * 60 * 60 * 24 * 7),
         bounty_bond: U128(0),                                                   // We changed this so the tests pass. We are not using bounties right now.
         bounty_forgiveness_period: U64::from(1_000_000_000 * 60 * 60 * 24),
+        max_revenue_table_length: default_max_revenue_table_length(),
+        max_payout_transfers: default_max_payout_transfers(),
+        platform_fee: 0,
     }
 }
 
@@ -279,6 +299,21 @@ impl Policy {
         if parameters.bounty_forgiveness_period.is_some() {
             self.bounty_forgiveness_period = parameters.bounty_forgiveness_period.unwrap();
         }
+        if parameters.max_revenue_table_length.is_some() {
+            let max_revenue_table_length = parameters.max_revenue_table_length.unwrap();
+            assert!(max_revenue_table_length > 0, "The maximum length of a RevenueTable has to be more than 0!");
+            self.max_revenue_table_length = max_revenue_table_length;
+        }
+        if parameters.max_payout_transfers.is_some() {
+            let max_payout_transfers = parameters.max_payout_transfers.unwrap();
+            assert!(max_payout_transfers > 0, "The number of payout transfers per call has to be more than 0!");
+            self.max_payout_transfers = max_payout_transfers;
+        }
+        if parameters.platform_fee.is_some() {
+            let platform_fee = parameters.platform_fee.unwrap();
+            assert!(platform_fee <= MAX_PLATFORM_FEE, "The platform fee can not be more than {} basis points!", MAX_PLATFORM_FEE);
+            self.platform_fee = platform_fee;
+        }
         env::log_str("Successfully updated the policy parameters.");
     }
 
@@ -606,6 +641,9 @@ mod tests {
             proposal_period: None,
             bounty_bond: None,
             bounty_forgiveness_period: Some(U64::from(1_000_000_000 * 60 * 60 * 24 * 5)),
+            max_revenue_table_length: None,
+            max_payout_transfers: Some(6),
+            platform_fee: None,
         };
         policy.update_parameters(&new_parameters);
         assert_eq!(U128(10u128.pow(26)), policy.proposal_bond);
@@ -618,5 +656,7 @@ mod tests {
             U64::from(1_000_000_000 * 60 * 60 * 24 * 5),
             policy.bounty_forgiveness_period
         );
+        assert_eq!(15, policy.max_revenue_table_length);
+        assert_eq!(6, policy.max_payout_transfers);
     }
 }
@@ -10,8 +10,8 @@ use near_sdk::serde_json::json;
 use crate::policy::UserInfo;
 use crate::types::{
     convert_old_to_new_token, Action, Config, OldAccountId, GAS_FOR_FT_TRANSFER, OLD_BASE_TOKEN,
-    ONE_YOCTO_NEAR, ScheduleMintParams, NftDataFromFrontEnd, MintingContractArgs, MintingContractMeta, MintingContractExtra,
-    RevenueTable, SalePriceInYoctoNear, TokenId, Payout
+    ONE_YOCTO_NEAR, MAX_ROYALTY, ScheduleMintParams, NftDataFromFrontEnd, NftDataPatch, MintingContractArgs, MintingContractMeta, MintingContractExtra,
+    AutoPayout, PriceSchedule, RevenueTable, SaleLimits, SalePriceInYoctoNear, SplitRules, TokenId, Payout
 };
 use crate::upgrade::{upgrade_remote, upgrade_using_factory};
 use crate::*;
@@ -56,6 +56,9 @@ pub struct PolicyParameters {
     pub proposal_period: Option<U64>,
     pub bounty_bond: Option<U128>,
     pub bounty_forgiveness_period: Option<U64>,
+    pub max_revenue_table_length: Option<u32>,
+    pub max_payout_transfers: Option<u32>,
+    pub platform_fee: Option<u32>,
 }
 
 /// Kinds of proposals, doing different action.
@@ -127,11 +130,41 @@ pub enum ProposalKind {
     /// Update the RevenueTable for a song that already has a RevenueTable
     AlterRevenueTable { tree_index: TreeIndex, unsafe_table: HashMap<AccountId, u64>, price: SalePriceInYoctoNear }, 
     /// Payout revenue for multiple songs, according to RevenueTable. If the caller does not have the right to initiate payout for some of the songs, that element will be ignored.
+    /// The result for each song is saved (see PayoutProgress). If the gas runs out, the payout can be continued with `continue_payout_revenue()`.
     PayoutRevenue { tree_index_list: Vec<TreeIndex> },
     /// Resend failed transaction to a different address. Only Council members can do this.
     ResendFailedTransaction { failed_id: u64, new_address: AccountId },
-    // **TODO** Not implemented
+    /// Schedule a ready InProgressMetadata to be minted at a given time. The mint is done by `execute_scheduled_mints()`
     ScheduleMint { params: ScheduleMintParams },
+    /// Allow a NEP-141 token to be used for buying NFTs
+    AddTokenToWhitelist { token_id: AccountId },
+    /// Remove a NEP-141 token from the whitelist. Prices that were already set in this token can't be used for buying anymore.
+    RemoveTokenFromWhitelist { token_id: AccountId },
+    /// Set (or remove, if price is None) the price of a song in a whitelisted NEP-141 token. Only the owner (Artist) can do this.
+    SetFtPrice { tree_index: TreeIndex, token_id: AccountId, price: Option<U128> },
+    /// Update only some of the fields of an InProgressMetadata object. Absent fields keep their old value, `null` clears the field. Uses the update_prepaired_nft permission.
+    PatchPrepairedNft { id: u64, patch: NftDataPatch },
+    /// Set the royalty of a song for secondary sales, in basis points. Only the owner (Artist) can do this.
+    SetRoyalty { tree_index: TreeIndex, royalty: u32 },
+    /// Set the sale window and the editions cap of a song. Only the owner (Artist) can do this.
+    SetSaleLimits { tree_index: TreeIndex, limits: SaleLimits },
+    /// Set (or remove, if schedule is None) the price schedule of a song. While there is a schedule, the price is calculated from it. Only the owner (Artist) can do this.
+    SetPriceSchedule { tree_index: TreeIndex, schedule: Option<PriceSchedule> },
+    /// Add accounts to, and remove accounts from the presale allowlist of a song. Only the owner (Artist) can do this.
+    UpdatePresaleAllowlist { tree_index: TreeIndex, add: Vec<AccountId>, remove: Vec<AccountId> },
+    /// Mark a pending failed transaction as written off, it won't be sent. The record is kept. Only Council members can do this.
+    WriteOffFailedTransaction { failed_id: u64 },
+    /// Set (or remove, if rules is None) the fixed amounts and thresholds that are used together with the RevenueTable. Only the owner (Artist) can do this.
+    SetSplitRules { tree_index: TreeIndex, rules: Option<SplitRules> },
+    /// Create or update a payee group (e.g. the members of a band), that can be used as a beneficiary in RevenueTables. Only the owner of the group can update it.
+    SetPayeeGroup { group_id: AccountId, unsafe_table: HashMap<AccountId, u64> },
+    /// Set (or remove, if auto_payout is None) the automatic payout rule of a song, see `distribute_due_revenue()`. Only the owner (Artist) can do this.
+    SetAutoPayout { tree_index: TreeIndex, auto_payout: Option<AutoPayout> },
+    /// Send collected platform fees from the DAO treasury balance to `receiver_id`. Only Council members can do this.
+    /// `token_id` is the NEP-141 token contract for fees of token sales, or None for $NEAR fees.
+    WithdrawPlatformFees { receiver_id: AccountId, amount: U128, token_id: Option<AccountId> },
+    /// Delete an InProgressMetadata object that won't be minted. The storage cost of the draft is refunded to the Artist.
+    DeletePreparedNft { id: u64 },
 }
 
 impl ProposalKind {
@@ -165,7 +198,21 @@ impl ProposalKind {
             ProposalKind::AlterRevenueTable { .. }  => "alter_revenue_table",
             ProposalKind::PayoutRevenue { .. } => "payout_revenue",
             ProposalKind::ResendFailedTransaction { .. } => "resend_failed_transaction",
-            ProposalKind::ScheduleMint { .. } => "schedule_mint"
+            ProposalKind::ScheduleMint { .. } => "schedule_mint",
+            ProposalKind::AddTokenToWhitelist { .. } => "add_token_to_whitelist",
+            ProposalKind::RemoveTokenFromWhitelist { .. } => "remove_token_from_whitelist",
+            ProposalKind::SetFtPrice { .. } => "set_ft_price",
+            ProposalKind::PatchPrepairedNft { .. } => "update_prepaired_nft",
+            ProposalKind::SetRoyalty { .. } => "set_royalty",
+            ProposalKind::SetSaleLimits { .. } => "set_sale_limits",
+            ProposalKind::SetPriceSchedule { .. } => "set_price_schedule",
+            ProposalKind::UpdatePresaleAllowlist { .. } => "update_presale_allowlist",
+            ProposalKind::WriteOffFailedTransaction { .. } => "write_off_failed_transaction",
+            ProposalKind::SetSplitRules { .. } => "set_split_rules",
+            ProposalKind::SetPayeeGroup { .. } => "set_payee_group",
+            ProposalKind::SetAutoPayout { .. } => "set_auto_payout",
+            ProposalKind::WithdrawPlatformFees { .. } => "withdraw_platform_fees",
+            ProposalKind::DeletePreparedNft { .. } => "delete_prepared_nft"
         }
     }
 }
@@ -313,6 +360,112 @@ impl Contract {
         }
     }
 
+    /// Sends the draft to the FonoRoot minting contract. The draft is kept in minting state, until `mint_root_callback` removes or restores it.
+    /// Used by the MintRoot proposal (`proposal_id` is Some) and by `execute_scheduled_mints()`. The callers have to validate the draft first.
+    pub(crate) fn internal_mint_root(&mut self, mut selected_draft: InProgressMetadata, proposal_id: Option<u64>) -> Promise {
+        let draft_id = selected_draft.id;
+        selected_draft.minting = true;
+        selected_draft.scheduled = None;                                            // The schedule is used up, even if minting fails
+        self.in_progress_nfts.insert(&draft_id, &selected_draft);
+
+        let extra = near_sdk::serde_json::to_string( &MintingContractExtra {        // extra will be a JSON string, that we will insert into the metadata
+            music_cid: Some(selected_draft.music.unwrap()),
+            music_hash: Some(selected_draft.music_hash.unwrap()),
+            animation_url: Some(selected_draft.animation_url.unwrap()),
+            animation_url_hash: Some(selected_draft.animation_url_hash.unwrap()),
+            parent: None,
+            next_buyable: None,
+            instance_nonce: 999_999_999,
+            generation: 999_999_999,
+        }).unwrap();
+
+        // The draft was validated by the caller (see `InProgressMetadata::validate()`), so the fields exist, and the .unwrap() calls can not fail
+        let args = MintingContractArgs {
+            receiver_id: selected_draft.artist.clone(),
+            metadata: MintingContractMeta { 
+                title: selected_draft.title.unwrap(),
+                description: selected_draft.desc.unwrap(),
+                reference: selected_draft.meta.unwrap(),
+                reference_hash: Some(selected_draft.meta_hash.unwrap()),
+                media: selected_draft.image.unwrap(),
+                media_hash: Some(selected_draft.image_hash.unwrap()),
+                copies: None,                                             // Will be None, we are not using this.
+                issued_at: None,                                          // Will be None, we are not using this.
+                expires_at: None,                                         // Will be None, we are not using this.
+                starts_at: None,                                          // Will be None, we are not using this.
+                updated_at: None,                                         // Will be None, we are not using this.
+                extra: Some(extra)
+            }
+        };
+        
+        let json_args = near_sdk::serde_json::to_string(&args).unwrap();            // This is a string
+        let base64_args = json_args.clone().into_bytes();                           // This is a Base64 byte array
+        
+        let mut promise = Promise::new(selected_draft.contract.clone());                    // Promise object created
+        
+        let action = ActionCall {                                                   // We are calling 'mint_root', we are depositing 0.2 NEAR,
+            method_name: "mint_root".to_string(),                                   // but will receive back the money that is not used for storage
+            args: base64_args.into(),
+            deposit: U128(200_000_000_000_000_000_000_000),
+            gas: U64(100_000_000_000_000),
+        };
+        
+        log!("Prepairing cross-contract call...");
+        
+        promise = promise.function_call(
+            action.method_name.clone().into(),
+            action.args.clone().into(),
+            action.deposit.0,
+            Gas(action.gas.0),
+        )
+        .then(ext_self::mint_root_callback(                                         // 'mint_root_callback' will run after the NFT was created,
+            selected_draft.artist,                                                  // which is in lib.rs
+            draft_id,
+            proposal_id,
+            env::current_account_id(),
+            0,
+            Gas(50_000_000_000_000)
+        ));
+        
+        log!("Initiating cross-contract call! Function inside DAO contract exiting...");
+        promise
+    }
+
+    /// Transfers `amount` to `receiver`, in $NEAR (token_id is None) or in a NEP-141 token, without a callback.
+    pub(crate) fn internal_transfer(receiver: AccountId, amount: Balance, token_id: &Option<AccountId>) -> Promise {
+        match token_id {
+            None => Promise::new(receiver).transfer(amount),
+            Some(token) => ext_fungible_token::ft_transfer(
+                receiver,
+                U128(amount),
+                Some("FonoRoot revenue payout".to_string()),
+                token.clone(),
+                ONE_YOCTO_NEAR,
+                GAS_FOR_FT_TRANSFER,
+            ),
+        }
+    }
+
+    /// Sends revenue (or a refund) to a beneficiary, in $NEAR (token_id is None) or in a NEP-141 token.
+    /// `transfer_callback` will save the transaction as a FailedTransaction, if the transfer fails.
+    pub(crate) fn internal_send_revenue(&self, beneficiary: AccountId, amount: Balance, token_id: Option<AccountId>) -> Promise {
+        let transfer = Self::internal_transfer(beneficiary.clone(), amount, &token_id);
+
+        transfer.then(
+            Promise::new(env::current_account_id())
+            .function_call(
+                "transfer_callback".to_string(),
+                json!({
+                    "beneficiary": beneficiary,
+                    "amount": U128(amount),
+                    "token_id": token_id
+                }).to_string().as_bytes().to_vec(),
+                0,
+                Gas(2_000_000_000_000)
+            )
+        )
+    }
+
     fn internal_return_bonds(&mut self, policy: &Policy, proposal: &Proposal) -> Promise {
         match &proposal.kind {
             ProposalKind::BountyDone { .. } => {
@@ -438,75 +591,19 @@ impl Contract {
             ProposalKind::MintRoot { id } => {
                 log!("Entering MintRoot, InProgress ID: {}", id);
 
-                let selected_draft = self.in_progress_nfts.remove(id).unwrap();             // If this contract call is successfull, the draft will be removed from the list
-                let fonoroot: AccountId =  selected_draft.contract;
-                self.assert_artist_can_mint(fonoroot.clone());                              // Artist needs to be member of the master group of the minting contract
+                let selected_draft = self.in_progress_nfts.get(id).unwrap();                // If this contract call is successfull, the draft will be removed by the callback
+                assert!(!selected_draft.minting, "The draft is already being minted!");
+                self.assert_artist_can_mint(selected_draft.contract.clone());               // Artist needs to be member of the master group of the minting contract
                 assert_eq!{                                                                 // The caller has to be the creator of the draft, otherwise the caller is not allowed to mint
                     env::predecessor_account_id(),
                     selected_draft.artist,
                     "Only the owner of the draft can mint!"
                 };
-                
-                let extra = near_sdk::serde_json::to_string( &MintingContractExtra {        // extra will be a JSON string, that we will insert into the metadata
-                    music_cid: Some(selected_draft.music.unwrap()),
-                    music_hash: Some(selected_draft.music_hash.unwrap()),
-                    animation_url: Some(selected_draft.animation_url.unwrap()),
-                    animation_url_hash: Some(selected_draft.animation_url_hash.unwrap()),
-                    parent: None,
-                    next_buyable: None,
-                    instance_nonce: 999_999_999,
-                    generation: 999_999_999,
-                }).unwrap();
-
-                // That validation that the param exists is already done by .unwrap() We could also validate title and description length, and possible the CID and the hash format.
-                // We will skip these validations for know, because we trust that the Artist is not hacking the front end (Artist has to be approved by Council)
-                let args = MintingContractArgs {                                  // By .unwrap(), we are validating that the parameter exists
-                    receiver_id: selected_draft.artist.clone(),
-                    metadata: MintingContractMeta { 
-                        title: selected_draft.title.unwrap(),
-                        description: selected_draft.desc.unwrap(),
-                        reference: selected_draft.meta.unwrap(),
-                        reference_hash: Some(selected_draft.meta_hash.unwrap()),
-                        media: selected_draft.image.unwrap(),
-                        media_hash: Some(selected_draft.image_hash.unwrap()),
-                        copies: None,                                             // Will be None, we are not using this.
-                        issued_at: None,                                          // Will be None, we are not using this.
-                        expires_at: None,                                         // Will be None, we are not using this.
-                        starts_at: None,                                          // Will be None, we are not using this.
-                        updated_at: None,                                         // Will be None, we are not using this.
-                        extra: Some(extra)
-                    }
-                };
-                
-                let json_args = near_sdk::serde_json::to_string(&args).unwrap();            // This is a string
-                let base64_args = json_args.clone().into_bytes();                           // This is a Base64 byte array
-                
-                let mut promise = Promise::new(fonoroot.clone().into());                    // Promise object created
-                
-                let action = ActionCall {                                                   // We are calling 'mint_root', we are depositing 0.2 NEAR,
-                    method_name: "mint_root".to_string(),                                   // but will receive back the money that is not used for storage
-                    args: base64_args.into(),
-                    deposit: U128(200_000_000_000_000_000_000_000),
-                    gas: U64(100_000_000_000_000),
-                };
-                
-                log!("Prepairing cross-contract call...");
-                
-                promise = promise.function_call(
-                    action.method_name.clone().into(),
-                    action.args.clone().into(),
-                    action.deposit.0,
-                    Gas(action.gas.0),
-                )
-                .then(ext_self::mint_root_callback(                                         // 'mint_root_callback' will run after the NFT was created,
-                    selected_draft.artist,                                                  // which is in lib.rs
-                    env::current_account_id(),
-                    0,
-                    Gas(50_000_000_000_000)
-                ));
-                
-                log!("Initiating cross-contract call! Function inside DAO contract exiting...");
-                promise.into()
+                let errors = selected_draft.validate();                                     // The draft could have been updated since the proposal was added
+                assert!(errors.is_empty(), "The draft can not be minted: {:?}", errors);
+                self.internal_unschedule_mint(&selected_draft);                             // Minting by hand cancels the schedule, if there was one
+
+                self.internal_mint_root(selected_draft, Some(proposal_id)).into()
             }
             ProposalKind::PrepareNft { nft_data } => {
                 self.assert_artist_can_mint(nft_data.contract.clone());                     // Artist needs to be member of the master group of the minting contract
@@ -530,6 +627,8 @@ impl Contract {
                     artist: env::predecessor_account_id(),
                     contract: nft_data.contract.clone(),
                     scheduled: None,
+                    minting: false,
+                    storage_cost: 0,
                     title: nft_data.title.clone(),
                     desc: nft_data.desc.clone(),
                     image: nft_data.image_cid.clone(),
@@ -542,7 +641,9 @@ impl Contract {
                     meta_hash: nft_data.meta_json_hash.clone()
                 };
 
-                self.in_progress_nfts.insert(&self.in_progress_nonce, &the_new_nft_data);
+                let initial_storage = env::storage_usage();
+                self.internal_insert_draft(&the_new_nft_data);
+                self.internal_charge_draft_storage(the_new_nft_data.id, initial_storage);  // The Artist pays for the storage of the draft
                 self.in_progress_nonce = self.in_progress_nonce + 1;
                 PromiseOrValue::Value(())
             }
@@ -555,6 +656,7 @@ impl Contract {
                     &env::predecessor_account_id(),
                     "You can only update prepared NFTs that you originally created!"
                 );
+                assert!(!old_data.minting, "The draft is being minted, it can not be updated!");
 
                 if new_nft_data.image_cid.is_some()  {                                      // Assertations about the existence of the hash values, for each CID
                     assert!(new_nft_data.image_hash.is_some(), "Hash has to exist, if image exists!");
@@ -575,6 +677,8 @@ impl Contract {
                     artist: env::predecessor_account_id(),
                     contract: new_nft_data.contract.clone(),
                     scheduled: old_data.scheduled,
+                    minting: false,
+                    storage_cost: old_data.storage_cost,
                     title: new_nft_data.title.clone(),
                     desc: new_nft_data.desc.clone(),
                     image: new_nft_data.image_cid.clone(),
@@ -587,7 +691,43 @@ impl Contract {
                     meta_hash: new_nft_data.meta_json_hash.clone()
                 };
 
-                self.in_progress_nfts.insert(id, &updated_nft_data);
+                let initial_storage = env::storage_usage();
+                self.internal_insert_draft(&updated_nft_data);
+                self.internal_charge_draft_storage(*id, initial_storage);                  // The draft can grow or shrink
+
+                PromiseOrValue::Value(())
+            },
+            ProposalKind::PatchPrepairedNft { id, patch } => {
+                let mut draft = self.in_progress_nfts.get(&id).expect("Draft does not exist!");
+                assert_eq!(
+                    &draft.artist,
+                    &env::predecessor_account_id(),
+                    "You can only update prepared NFTs that you originally created!"
+                );
+                assert!(!draft.minting, "The draft is being minted, it can not be updated!");
+
+                draft.apply_patch(patch);
+                self.assert_artist_can_mint(draft.contract.clone());                        // The patch might move the draft to another minting contract
+                draft.assert_hashes_exist();                                                // Checked on the merged draft, the hash could be sent in an earlier update
+
+                let initial_storage = env::storage_usage();
+                self.internal_insert_draft(&draft);
+                self.internal_charge_draft_storage(*id, initial_storage);
+
+                PromiseOrValue::Value(())
+            },
+            ProposalKind::DeletePreparedNft { id } => {
+                let draft = self.in_progress_nfts.get(&id).expect("Draft does not exist!");
+                assert_eq!(
+                    &draft.artist,
+                    &env::predecessor_account_id(),
+                    "You can only delete prepared NFTs that you originally created!"
+                );
+                assert!(!draft.minting, "The draft is being minted, it can not be deleted!");
+
+                self.internal_unschedule_mint(&draft);                                      // Deleting cancels the schedule, if there was one
+                self.internal_remove_draft(*id);                                            // This will refund the storage cost
+                log!("InProgressNft {} was deleted.", id);
 
                 PromiseOrValue::Value(())
             },
@@ -595,7 +735,7 @@ impl Contract {
                 let uniq_id = UniqId::new(contract.clone(), root_id.clone());               // Will calculate an ID like minting-contract.near-fono-root-5
                 let tree_index = self.uniq_id_to_tree_index.get(&uniq_id.clone()).unwrap();
                 let mut income_table = self.income_tables.get(&tree_index.clone()).unwrap();
-                let revenue_table = RevenueTable::new(unsafe_table.clone()).unwrap();       // RevenueTable type is doing validation
+                let revenue_table = RevenueTable::new(unsafe_table.clone(), policy.max_revenue_table_length).unwrap();       // RevenueTable type is doing validation
                 log!("Creating RevenueTable for UniqId {:?}, for which the TreeIndex is {}", uniq_id.clone(), tree_index.clone());
                 
                 // Prepair Revenue Entry, mint_root_callback() creates this entry, if does not exist, panic.
@@ -613,6 +753,7 @@ impl Contract {
                 
                 let new_entry = CatalogueEntry {
                     revenue_table: revenue_table.clone(),
+                    split_rules: None,
                 };
                 income_table.price = Some(price.clone());
                 self.income_tables.insert(&tree_index.clone(), &income_table);              // We insert back the IncomeTable, that contains the price now
@@ -625,7 +766,7 @@ impl Contract {
             },
             ProposalKind::AlterRevenueTable { tree_index, unsafe_table, price } => {
                 log!("Updating RevenueTable with TreeIndex: {}", tree_index);
-                let new_revenue_table = RevenueTable::new(unsafe_table.clone()).unwrap();
+                let new_revenue_table = RevenueTable::new(unsafe_table.clone(), policy.max_revenue_table_length).unwrap();
                 let mut income_table = self.income_tables.get(&tree_index.clone()).unwrap();
 
                 // Prepair Revenue Entry, mint_root_callback() creates this entry, if does not exist, panic.
@@ -639,6 +780,7 @@ impl Contract {
 
                 let new_entry = CatalogueEntry {
                     revenue_table: new_revenue_table.clone(),
+                    split_rules: catalogue_for_caller.get(&tree_index).flatten().and_then(|entry| entry.split_rules),  // The split rules are kept
                 };
                 income_table.price = Some(price.clone());
                 self.income_tables.insert(&tree_index.clone(), &income_table);              // We insert back the IncomeTable, that contains the price now
@@ -655,75 +797,244 @@ impl Contract {
                     account_id: env::signer_account_id(),
                     amount: 0
                 };
-                let policy = self.policy.get().unwrap().to_policy();
                 let is_admin = policy.get_user_roles(user).contains_key(&"council".to_string());
                 log!("Caller is an admin: {}", is_admin);
-                let mut could_not_pay_out: Vec<TreeIndex> = Vec::new();                           // List of IncomeTables that could not be payed out (by TreeIndex)
-                
-                for index in tree_index_list {
-                    let mut current_table = self.income_tables.get(&index).unwrap();
-                    let is_owner = current_table.owner == env::signer_account_id();
-                    if is_owner || is_admin {                                                     // RevenueTable payout happens if caller is owner or Council member
-                        let owner_catalogue = self.catalogues.get(&current_table.owner).unwrap();
-                        let entry = owner_catalogue.get(&index).unwrap().unwrap();                // This is a CatalogueEntry struct
-                        
-                        let payout_table = self.generate_payout_object(                           // Will contain amounts in yoctoNEAR
-                            entry.revenue_table,
-                            current_table.current_balance,
-                            6
-                        );
-
-                        for (key, amount) in payout_table.payout.iter() {                         // Send the money to each account on the list
-                            let beneficiary = key.clone();
-                            log!("Sending {} yoctoNEAR to {} ...", u128::from(amount.clone()), beneficiary);
-                            Promise::new(beneficiary.clone()).transfer(u128 ::from(amount.clone())).then(
-                                Promise::new(env::current_account_id())
-                                .function_call(
-                                    "transfer_callback".to_string(), 
-                                    json!({
-                                        "beneficiary": beneficiary, 
-                                        "amount": amount 
-                                    }).to_string().as_bytes().to_vec(), 
-                                    0, 
-                                    Gas(2_000_000_000_000
-                                ))
-                            );
-                        }
-
-                        current_table.current_balance = 0;
-                        self.income_tables.insert(&index, &current_table);
-                        log!("Current balance for TreeIndex {} was nulled.", index);
-                    } else {
-                        could_not_pay_out.push(index.clone());
-                        log!("The IncomeTable with TreeIndex {} could not be paid out, because the caller is neither the owner, nor a council member.", index);
-                    }
-                }
-                log!("These are the TreeIndexes that were not paid out: {:?}", could_not_pay_out);
+
+                let mut progress = PayoutProgress {
+                    executor: env::signer_account_id(),
+                    is_admin: is_admin,
+                    cursor: 0,
+                    results: Vec::new(),
+                    finished: false,
+                };
+                self.internal_run_payout(proposal_id, tree_index_list, &mut progress);        // Stops when the gas runs out, see continue_payout_revenue()
 
                 PromiseOrValue::Value(())
             },
             ProposalKind::ResendFailedTransaction { failed_id, new_address } => {
-                let the_failed_transaction = self.failed_transactions.remove(&failed_id).unwrap();
-                let old_address = the_failed_transaction.beneficiary;
-                let amount = the_failed_transaction.amount;
-
+                let old_address = self.failed_transactions.get(failed_id).expect("Failed transaction not found!").beneficiary;
                 log!("Resending transaction. Old address: {} New address: {}", old_address, new_address);
-                log!("Sending {} yoctoNEAR to {} ...", u128::from(amount.clone()), new_address);
-                
-                Promise::new(new_address.clone()).transfer(u128 ::from(amount.clone())).then(
-                    Promise::new(env::current_account_id())
-                    .function_call("transfer_callback".to_string(), json!({ "beneficiary": new_address, "amount": U128::from(amount) }).to_string().as_bytes().to_vec(), 0, Gas(2_000_000_000_000))
+
+                self.internal_resend_failed_transaction(*failed_id, new_address.clone());   // The record is kept until the resend succeeds
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::WriteOffFailedTransaction { failed_id } => {
+                let mut failed_transaction = self.failed_transactions.get(failed_id).expect("Failed transaction not found!");
+                assert_eq!(
+                    failed_transaction.status,
+                    FailedTransactionStatus::Pending,
+                    "Only pending failed transactions can be written off!"
+                );
+
+                failed_transaction.set_status(FailedTransactionStatus::WrittenOff, None);
+                self.internal_update_failed_transaction(*failed_id, &failed_transaction);
+                log!("Failed transaction {} was written off.", failed_id);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetSplitRules { tree_index, rules } => {
+                let income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the split rules!"
                 );
+                let mut catalogue_for_caller = self.catalogues.get(&income_table.owner).unwrap();
+                let mut entry = catalogue_for_caller.get(&tree_index).flatten().expect("The song does not have a RevenueTable yet!");
+                if let Some(rules) = rules {
+                    rules.assert_valid(policy.max_revenue_table_length);
+                }
+
+                entry.split_rules = rules.clone();
+                catalogue_for_caller.insert(&tree_index, &Some(entry));
+                self.catalogues.insert(&income_table.owner, &catalogue_for_caller);
+                log!("Split rules for TreeIndex {} were set to {:?}", tree_index, rules);
 
                 PromiseOrValue::Value(())
             }
-            ProposalKind::ScheduleMint { params: _ } => {
-                //self.assert_artist_can_mint(nft_data.contract.clone());
+            ProposalKind::SetPayeeGroup { group_id, unsafe_table } => {
+                let revenue_table = RevenueTable::new(unsafe_table.clone(), policy.max_revenue_table_length).expect("The RevenueTable of the payee group is not valid!");
+                self.internal_set_payee_group(group_id, revenue_table);
+                log!("Payee group {} was set: {:?}", group_id, self.payee_groups.get(group_id).unwrap());
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetAutoPayout { tree_index, auto_payout } => {
+                let mut income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the automatic payout!"
+                );
+                if let Some(rule) = auto_payout {
+                    assert!(rule.threshold.is_some() || rule.period.is_some(), "The automatic payout needs a threshold or a period!");
+                    assert!(rule.threshold.map_or(true, |threshold| threshold.0 > 0), "The threshold has to be more than 0!");
+                    assert!(rule.period.map_or(true, |period| period.0 > 0), "The period has to be more than 0!");
+                }
+
+                income_table.auto_payout = auto_payout.clone();
+                self.income_tables.insert(&tree_index, &income_table);
+                log!("Automatic payout for TreeIndex {} was set to {:?}", tree_index, auto_payout);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::WithdrawPlatformFees { receiver_id, amount, token_id } => {
+                assert!(amount.0 > 0, "The amount has to be more than 0!");
+                match token_id {
+                    None => {
+                        assert!(amount.0 <= self.platform_fee_balance, "The amount is more than the platform fee balance!");
+                        self.platform_fee_balance = self.platform_fee_balance - amount.0;
+                    }
+                    Some(token_id) => {
+                        let balance = self.ft_platform_fee_balances.get(token_id).copied().unwrap_or(0);
+                        assert!(amount.0 <= balance, "The amount is more than the platform fee balance!");
+                        self.ft_platform_fee_balances.insert(token_id.clone(), balance - amount.0);
+                    }
+                }
+
+                log!("Withdrawing {} (token: {:?}) platform fees to {}", amount.0, token_id, receiver_id);
+                self.internal_send_revenue(receiver_id.clone(), amount.0, token_id.clone()); // If the transfer fails, it is saved as a FailedTransaction
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::ScheduleMint { params } => {
+                let mut draft = self.in_progress_nfts.get(&params.id).unwrap();
+                self.assert_artist_can_mint(draft.contract.clone());                        // Artist needs to be member of the master group of the minting contract
+                assert_eq!(
+                    draft.artist,
+                    env::predecessor_account_id(),
+                    "Only the owner of the draft can schedule the mint!"
+                );
+                assert!(draft.is_ready(), "The draft is not ready to be minted!");          // execute_scheduled_mints() should not fail on missing data
+                assert!(!draft.minting, "The draft is already being minted!");
+                assert!(
+                    params.timestamp.0 > env::block_timestamp(),
+                    "The scheduled time has to be in the future!"
+                );
+
+                self.internal_unschedule_mint(&draft);                                      // A new time replaces the old one
+                draft.scheduled = Some(params.timestamp.0);
+                self.in_progress_nfts.insert(&params.id, &draft);
+                self.scheduled_mints.insert(&(params.timestamp.0, params.id), &());
+                log!("InProgressNft {} is scheduled to be minted at {}", params.id, params.timestamp.0);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::AddTokenToWhitelist { token_id } => {
+                self.ft_whitelist.insert(token_id);
+                log!("Token {} was added to the whitelist.", token_id);
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::RemoveTokenFromWhitelist { token_id } => {
+                self.ft_whitelist.remove(token_id);
+                log!("Token {} was removed from the whitelist.", token_id);
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetFtPrice { tree_index, token_id, price } => {
+                let mut income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the price!"
+                );
+
+                match price {
+                    Some(price) => {
+                        assert!(self.ft_whitelist.contains(token_id), "This token is not whitelisted!");
+                        income_table.ft_prices.insert(token_id.clone(), price.clone());
+                    }
+                    None => {
+                        income_table.ft_prices.remove(token_id);
+                    }
+                }
+                self.income_tables.insert(&tree_index, &income_table);
+                log!("Price in {} for TreeIndex {} was set to {:?}", token_id, tree_index, price);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetRoyalty { tree_index, royalty } => {
+                let mut income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the royalty!"
+                );
+                assert!(*royalty <= MAX_ROYALTY, "The royalty can not be more than {} basis points!", MAX_ROYALTY);
+
+                income_table.royalty = *royalty;
+                self.income_tables.insert(&tree_index, &income_table);
+                log!("Royalty for TreeIndex {} was set to {} basis points", tree_index, royalty);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetSaleLimits { tree_index, limits } => {
+                let mut income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the sale limits!"
+                );
+                if let (Some(start), Some(end)) = (limits.start, limits.end) {
+                    assert!(start.0 < end.0, "The end of the sale has to be after the start!");
+                }
+                if let Some(max_editions) = limits.max_editions {
+                    assert!(
+                        max_editions >= income_table.sold + income_table.pending_sales,
+                        "The editions cap can not be less than the number of NFTs that were already sold!"
+                    );
+                }
+
+                income_table.sale_limits = limits.clone();
+                self.income_tables.insert(&tree_index, &income_table);
+                log!("Sale limits for TreeIndex {} were set to {:?}", tree_index, limits);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::SetPriceSchedule { tree_index, schedule } => {
+                let mut income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the price!"
+                );
+                if let Some(schedule) = schedule {
+                    schedule.assert_valid();
+                }
+
+                income_table.price_schedule = schedule.clone();
+                self.income_tables.insert(&tree_index, &income_table);
+                log!("Price schedule for TreeIndex {} was set to {:?}", tree_index, schedule);
+
+                PromiseOrValue::Value(())
+            }
+            ProposalKind::UpdatePresaleAllowlist { tree_index, add, remove } => {
+                let income_table = self.income_tables.get(&tree_index).unwrap();
+                assert_eq!(
+                    income_table.owner,
+                    env::signer_account_id(),
+                    "Only the owner (Artist) can alter the presale allowlist!"
+                );
+
+                let mut allowlist = self.presale_allowlists.get(&tree_index).unwrap_or_else(|| {
+                    UnorderedSet::new(StorageKeys::PresaleAllowlist(*tree_index))
+                });
+                for account in add.iter() {
+                    allowlist.insert(account);
+                }
+                for account in remove.iter() {
+                    allowlist.remove(account);
+                }
+                self.presale_allowlists.insert(&tree_index, &allowlist);
+                log!("Presale allowlist for TreeIndex {} was updated, {} accounts are on the list", tree_index, allowlist.len());
 
                 PromiseOrValue::Value(())
             }
         };
         match result {
+            PromiseOrValue::Promise(promise) if matches!(proposal.kind, ProposalKind::MintRoot { .. }) => {
+                promise.into()                                                              // mint_root_callback will finalize the proposal
+            }
             PromiseOrValue::Promise(promise) => promise
                 .then(ext_self::on_proposal_callback(
                     proposal_id,
@@ -763,6 +1074,27 @@ impl Contract {
         PromiseOrValue::Value(())
     }
 
+    /// Finalizes a MintRoot proposal from `mint_root_callback`. If `failure_reason` is Some, the proposal will be Failed,
+    /// and the Artist can retry with Action::Finalize (the proposal will be executed again, if it is still approved)
+    pub(crate) fn internal_finalize_mint_proposal(&mut self, proposal_id: u64, failure_reason: Option<String>) {
+        let mut proposal: Proposal = match self.proposals.get(&proposal_id) {
+            Some(proposal) => proposal.into(),
+            None => return,
+        };
+
+        match failure_reason {
+            Some(reason) => {
+                self.internal_callback_proposal_fail(&mut proposal);
+                self.proposal_failures.insert(&proposal_id, &reason);
+            }
+            None => {
+                self.internal_callback_proposal_success(&mut proposal);
+                self.proposal_failures.remove(&proposal_id);
+            }
+        }
+        self.proposals.insert(&proposal_id, &VersionedProposal::Default(proposal));
+    }
+
     /// Process rejecting proposal.
     fn internal_reject_proposal(
         &mut self,
@@ -799,6 +1131,7 @@ impl Contract {
     /// Add proposal to this DAO.
     #[payable]
     pub fn add_proposal(&mut self, proposal: ProposalInput) -> u64 {
+        self.assert_not_migrating();
         // 0. validate bond attached.
         // TODO: consider bond in the token of this DAO.
         let policy = self.policy.get().unwrap().to_policy();
@@ -825,6 +1158,12 @@ impl Contract {
                 self.staking_id.is_none(),
                 "ERR_STAKING_CONTRACT_CANT_CHANGE"
             ),
+            ProposalKind::MintRoot { id } => {                                              // MintRoot would fail on execution, if the draft is not valid
+                let draft = self.in_progress_nfts.get(id).expect("Draft does not exist!");
+                assert!(!draft.minting, "The draft is already being minted!");
+                let errors = draft.validate();
+                assert!(errors.is_empty(), "The draft can not be minted: {:?}", errors);
+            }
             // TODO: add more verifications.
             _ => {}
         };
@@ -853,6 +1192,7 @@ impl Contract {
     /// Act on given proposal by id, if permissions allow.
     /// Memo is logged but not stored in the state. Can be used to leave notes or explain the action.
     pub fn act_proposal(&mut self, id: u64, action: Action, memo: Option<String>) {
+        self.assert_not_migrating();
         let mut proposal: Proposal = self.proposals.get(&id).expect("ERR_NO_PROPOSAL").into();
         let policy = self.policy.get().unwrap().to_policy();
         // Check permissions for the given action.
@@ -983,22 +1323,60 @@ impl Contract {
         assert!(false, "The role was not found.");
     }
 
-    /// Helper function that creats a revenue payout object
-    pub fn generate_payout_object(&self, revenue: RevenueTable, price: Balance, max_len_payout: u32) -> Payout {
-        let mut total = 0;
-        let mut payout_object = Payout {
-            payout: HashMap::new()
+    /// Returns true if the artist is member of the master group of the minting contract. Unlike `assert_artist_can_mint()`, this does not panic.
+    pub(crate) fn internal_artist_can_mint(&self, artist: &AccountId, contract_name: &AccountId) -> bool {
+        let master_group = "master_".to_string() + &contract_name.to_string();
+        let artist = UserInfo {
+            account_id: artist.clone(),
+            amount: 0
         };
-        
-        assert!(revenue.len() as u32 <= max_len_payout, "The contract cannot payout to that many receivers");
+        let policy = self.policy.get().unwrap().to_policy();
 
-        for (key, percent) in revenue.into_iter() {
-            let beneficiary = key.clone();
-            payout_object.payout.insert(beneficiary, U128(percent as u128 * price / 10_000u128));
-            total += u128::from(percent);
-        }
+        policy.roles.iter().any(|role| role.name == master_group && role.kind.match_user(&artist))
+    }
+
+    /// Splits `amount` $NEAR revenue of a song, with the SplitRules of the CatalogueEntry, or with the RevenueTable if there are no rules.
+    /// The cumulative split revenue and the recouped amounts are updated in `income_table`, the caller has to save it.
+    /// The parts are not sent from one receipt, so the number of receivers is not limited here (see the payout queue in `distribution.rs`).
+    /// Returns an error instead of panicking, because it runs in callbacks. `income_table` is only changed if the split was successful.
+    pub(crate) fn generate_split_payout(&self, entry: &CatalogueEntry, income_table: &mut IncomeTable, amount: Balance) -> Result<Payout, String> {
+        let payout_object = match &entry.split_rules {
+            None => self.internal_try_payout_object(&entry.revenue_table, amount)?,
+            Some(rules) => {
+                let mut recouped = income_table.recouped.clone();
+                let parts = self.internal_try_expand_payee_groups(
+                    rules.split(&entry.revenue_table, amount, income_table.revenue_split, &mut recouped)
+                )?;
+                income_table.recouped = recouped;
+                Payout {
+                    payout: parts.into_iter().map(|(beneficiary, part)| (beneficiary, U128(part))).collect()
+                }
+            }
+        };
+
+        let split: Balance = payout_object.payout.values().map(|part| part.0).sum();
+        income_table.revenue_split = income_table.revenue_split + split;
+        Ok(payout_object)
+    }
+
+    /// Splits `amount` with the RevenueTable, payee groups are split further between their members. The number of receivers is not limited.
+    pub(crate) fn internal_try_payout_object(&self, revenue: &RevenueTable, amount: Balance) -> Result<Payout, String> {
+        let parts = self.internal_try_expand_payee_groups(revenue.split(amount))?;
+        Ok(Payout {
+            payout: parts.into_iter().map(|(beneficiary, part)| (beneficiary, U128(part))).collect()
+        })
+    }
 
-        assert_eq!(total, 10000, "Total should be 100%!");
+    /// Helper function that creats a revenue payout object. Payee groups in the table are split further between their members.
+    pub fn generate_payout_object(&self, revenue: RevenueTable, price: Balance, max_len_payout: u32) -> Payout {
+        assert!(revenue.len() as u32 <= max_len_payout, "The contract cannot payout to that many receivers");
+        assert_eq!(revenue.values().sum::<u64>(), 10000, "Total should be 100%!");
+
+        let parts = revenue.split(price);                                                   // The parts add up to `price`, nothing is lost to rounding
+        let payout_object = Payout {
+            payout: self.internal_expand_payee_groups(parts).into_iter().map(|(beneficiary, part)| (beneficiary, U128(part))).collect()
+        };
+        assert!(payout_object.payout.len() as u32 <= max_len_payout, "The contract cannot payout to that many receivers");
 
         payout_object
     }
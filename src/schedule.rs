@@ -0,0 +1,62 @@
+use crate::*;
+
+/// Gas that is needed for a single scheduled mint (`mint_root` call + `mint_root_callback`)
+const GAS_FOR_SCHEDULED_MINT: Gas = Gas(160_000_000_000_000);
+
+#[near_bindgen]
+impl Contract {
+    /// Mint the scheduled InProgressNfts, for which the release time has passed. At most `limit` NFTs will be minted.
+    /// Anyone can call this function, the schedule was already approved through the ScheduleMint proposal.
+    /// Returns the list of InProgressNft IDs that were sent to the minting contract.
+    pub fn execute_scheduled_mints(&mut self, limit: u64) -> Vec<u64> {
+        self.assert_not_migrating();
+        let now = env::block_timestamp();
+        let due_list: Vec<(u64, u64)> = self.scheduled_mints
+            .iter()                                                                         // Ordered by release time, so we stop at the first one that is not due
+            .map(|(key, _)| key)
+            .take_while(|(release_time, _)| *release_time <= now)
+            .take(limit as usize)
+            .collect();
+
+        let mut minted: Vec<u64> = Vec::new();
+        for (release_time, id) in due_list {
+            if env::prepaid_gas() - env::used_gas() < GAS_FOR_SCHEDULED_MINT {
+                log!("Not enough gas left for the next scheduled mint, the rest will be minted by the next call.");
+                break;
+            }
+
+            self.scheduled_mints.remove(&(release_time, id));
+            let mut draft = match self.in_progress_nfts.get(&id) {
+                Some(draft) => draft,
+                None => continue,                                                           // Draft does not exist anymore, the entry is cleaned up
+            };
+            if draft.scheduled != Some(release_time) {
+                continue;
+            }
+
+            // The draft could have been updated, or the Artist could have been removed from the master group since it was scheduled.
+            // We don't want one bad draft to block the others, so we only cancel the schedule for it.
+            if draft.minting || !draft.is_ready() || !self.internal_artist_can_mint(&draft.artist, &draft.contract) {
+                log!("Scheduled mint for InProgressNft {} was cancelled, the draft is not ready, or the Artist can not mint anymore.", id);
+                draft.scheduled = None;
+                self.in_progress_nfts.insert(&id, &draft);
+                continue;
+            }
+
+            log!("Minting scheduled InProgressNft {}", id);
+            self.internal_mint_root(draft, None);
+            minted.push(id);
+        }
+
+        minted
+    }
+}
+
+impl Contract {
+    /// Removes the draft from the schedule, if it was scheduled. The `scheduled` field of the draft is not changed
+    pub(crate) fn internal_unschedule_mint(&mut self, draft: &InProgressMetadata) {
+        if let Some(release_time) = draft.scheduled {
+            self.scheduled_mints.remove(&(release_time, draft.id));
+        }
+    }
+}
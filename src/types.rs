@@ -1,7 +1,7 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::json_types::{Base64VecU8, U128};
-use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{AccountId, Balance, Gas, log};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Deserializer, Serialize};
+use near_sdk::{env, AccountId, Balance, Gas, log};
 use near_sdk::collections::{UnorderedMap};
 use std::collections::HashMap;
 use std::ops::Deref;
@@ -21,6 +21,27 @@ pub const ONE_YOCTO_NEAR: Balance = 1;
 /// Gas for single ft_transfer call.
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
 
+/// Maximum length of the title of an NFT, in bytes.
+pub const MAX_TITLE_LENGTH: usize = 256;
+
+/// Maximum length of the description of an NFT, in bytes.
+pub const MAX_DESC_LENGTH: usize = 4096;
+
+/// Storage deposit that is attached to `buy_nft_from_vault`, 0.1 NEAR. The buyer pays for this, on top of the price.
+pub const VAULT_STORAGE_DEPOSIT: Balance = 100_000_000_000_000_000_000_000;
+
+/// Maximum royalty for secondary sales, in basis points (5_000 is 50%).
+pub const MAX_ROYALTY: u32 = 5_000;
+
+/// Maximum platform fee of the DAO, in basis points (2_000 is 20%).
+pub const MAX_PLATFORM_FEE: u32 = 2_000;
+
+/// Length of the SHA256 hashes (image_hash, music_hash, etc.), in bytes.
+pub const HASH_LENGTH: usize = 32;
+
+/// Payee groups can contain other payee groups, but only this many levels deep.
+pub const MAX_PAYEE_GROUP_DEPTH: usize = 4;
+
 /// Configuration of the DAO.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -99,8 +120,12 @@ pub struct InProgressMetadata {
     pub artist: AccountId,
     /// Minting contract. The NFT will live on this contract.
     pub contract: AccountId,
-    /// **TODO** It is not sure that we will have this field. For this, we would need to implement the ScheduleMint proposal.
+    /// Release time set by the ScheduleMint proposal. If this is Some, the draft will be minted by `execute_scheduled_mints()` after this timestamp.
     pub scheduled: Option<u64>,
+    /// True while the `mint_root` call is in progress. The draft is removed when the NFT was created, and restored if minting failed.
+    pub minting: bool,
+    /// Storage cost of the draft in yoctoNEAR, charged from the draft storage balance of the Artist. Refunded when the draft is deleted or minted.
+    pub storage_cost: Balance,
     /// Title of the NFT, follows NFT standard
     pub title: Option<String>,
     /// Description of the NFT, follows NFT standard
@@ -138,6 +163,14 @@ pub struct BuyArgs {
     pub root_id: TokenId
 }
 
+/// This is the `msg` that has to be attached to `ft_transfer_call`, when an NFT is bought with a NEP-141 token
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBuyArgs {
+    pub root_id: TokenId,
+    pub minting_contract: AccountId,
+}
+
 /// Exact copy of TokenMetadata, from FonoRoot (that is following the NFT standard)
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -187,6 +220,44 @@ pub struct NftDataFromFrontEnd {
     pub meta_json_hash: Option<Base64VecU8>                     // If meta exists, we need hash for it as well
 }
 
+/// Patch for a draft, used by the PatchPrepairedNft proposal. Fields that are absent keep their old value.
+/// For the optional fields, `null` clears the field, so `{ "title": "New title" }` only changes the title, and `{ "animation_url": null, "animation_url_hash": null }` removes the animation.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftDataPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<AccountId>,                            // The minting contract can't be cleared, only changed
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub desc: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub image_cid: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub image_hash: Option<Option<Base64VecU8>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub music_folder_cid: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub music_folder_hash: Option<Option<Base64VecU8>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub animation_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub animation_url_hash: Option<Option<Base64VecU8>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub meta_json_cid: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option", skip_serializing_if = "Option::is_none")]
+    pub meta_json_hash: Option<Option<Base64VecU8>>,
+}
+
+/// Deserializes a field that is present in the JSON (even if it is `null`) as Some. Absent fields are None, because of `#[serde(default)]`
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 /// A Catalogue for an Artist. Each Artist has a Catalogue. In Contract, catalogues LookupMap is a list if Catalogue-s. (Artist AccountId is key)
 /// TreeIndex is unique, we can find the NFT in the income_tables with this index
 pub type Catalogue = UnorderedMap<TreeIndex, Option<CatalogueEntry>>;
@@ -196,11 +267,53 @@ pub type Catalogue = UnorderedMap<TreeIndex, Option<CatalogueEntry>>;
 #[serde(crate = "near_sdk::serde")]
 pub struct CatalogueEntry {                                     // Price used to be here, that's why this is a struct
     pub revenue_table: RevenueTable,                            // We will keep this a struct, because we might add more fields later.
+    pub split_rules: Option<SplitRules>,                        // Fixed amounts and thresholds on top of the RevenueTable. Set by the SetSplitRules proposal
 }
 
-// **TODO** This is just a placeholder
-// **TODO** We either need to keep ScheduleMint, or we need some kind of special Role, like CronCat, which is allowed to mint, even if it is not the Artist.
-pub type ScheduleMintParams = String;
+/// A named group of payees, for example the members of a band. A RevenueTable (or another group) can pay the group by its ID,
+/// and the amount is split further according to `revenue_table`. Only the owner can change the group.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayeeGroup {
+    pub owner: AccountId,
+    pub revenue_table: RevenueTable,
+}
+
+/// A fixed amount that is paid to `beneficiary` before the percentages, for example an advance that is recouped first
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FixedTier {
+    pub beneficiary: AccountId,
+    pub amount: U128,                                           // The tier is recouped when this much was paid to the beneficiary
+    pub max_share: Option<u32>,                                 // Recoupment cap: at most this much of each payout (basis points) goes to the tier
+}
+
+/// Percentages that are used instead of the RevenueTable, after `threshold` revenue of the song was split
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ThresholdSplit {
+    pub threshold: U128,
+    pub revenue_table: RevenueTable,
+}
+
+/// Split rules of a song. The $NEAR revenue first goes to the fixed tiers (in order), the rest is split by percentages:
+/// by the RevenueTable of the CatalogueEntry until the first threshold, and by the table of the last reached threshold after that.
+/// NEP-141 revenue is always split by the RevenueTable, because the fixed amounts are in yoctoNEAR.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SplitRules {
+    pub tiers: Vec<FixedTier>,
+    pub thresholds: Vec<ThresholdSplit>,                        // Ordered by threshold
+}
+
+/// Parameters for the ScheduleMint proposal. The draft with `id` will be minted when `timestamp` is reached.
+/// The minting itself is done by `execute_scheduled_mints()`, which can be called by anyone (for example a cron service)
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ScheduleMintParams {
+    pub id: u64,                                                // ID of the InProgressMetadata
+    pub timestamp: U64,                                         // Release time in nanoseconds, same format as env::block_timestamp()
+}
 
 /// IncomeTable is a very important object, that is used for other things as well, because this is most easily iterable.
 /// This is why the price is here. It might be a good idea to rename this, to something like _MainSongObject_
@@ -213,6 +326,244 @@ pub struct IncomeTable {
     pub contract: AccountId,
     pub owner: AccountId,
     pub price: Option<SalePriceInYoctoNear>,
+    /// Prices in whitelisted NEP-141 tokens (token contract -> price). The NFT can be bought with any of these tokens.
+    pub ft_prices: HashMap<AccountId, U128>,
+    /// Total income in NEP-141 tokens (token contract -> amount)
+    pub ft_total_income: HashMap<AccountId, Balance>,
+    /// Balances in NEP-141 tokens that were not paid out yet (token contract -> amount)
+    pub ft_balances: HashMap<AccountId, Balance>,
+    /// Royalty for secondary sales, in basis points (10_000 is 100%). It is split according to the RevenueTable, see `nft_payout()`
+    pub royalty: u32,
+    /// Sale window and editions cap, set by the SetSaleLimits proposal
+    pub sale_limits: SaleLimits,
+    /// Number of NFTs that were sold (with $NEAR or with a NEP-141 token)
+    pub sold: u64,
+    /// Number of purchases that are in progress (waiting for the callback). These count against `max_editions`
+    pub pending_sales: u64,
+    /// If this is Some, the price is calculated from the schedule, and `price` is not used. Set by the SetPriceSchedule proposal
+    pub price_schedule: Option<PriceSchedule>,
+    /// Cumulative $NEAR revenue that was split between the beneficiaries (claimable or paid out). The thresholds of the SplitRules use this
+    pub revenue_split: Balance,
+    /// Amount that the fixed tiers of the SplitRules received so far (beneficiary -> amount)
+    pub recouped: HashMap<AccountId, Balance>,
+    /// Automatic payout rule, set by the SetAutoPayout proposal. None means the song is only paid out by PayoutRevenue
+    pub auto_payout: Option<AutoPayout>,
+    /// Time of the last payout (or of the mint), the period of the AutoPayout is counted from this
+    pub last_payout: u64,
+}
+
+/// A step of the `PriceSchedule::Stepped` schedule. `price` is valid from `from`, until the next step
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceStep {
+    pub from: U64,
+    pub price: SalePriceInYoctoNear,
+}
+
+/// Price curve of a song. `buy_nft()` and `get_price()` calculate the current price from this at `env::block_timestamp()`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PriceSchedule {
+    /// The price does not change
+    Fixed { price: SalePriceInYoctoNear },
+    /// The price changes at given dates. Before the first step, the price of the first step is used
+    Stepped { steps: Vec<PriceStep> },
+    /// The price goes down linearly from `start_price` to `end_price`, between `start` and `end`
+    DutchAuction { start: U64, end: U64, start_price: SalePriceInYoctoNear, end_price: SalePriceInYoctoNear },
+    /// Until `presale_end` the (usually discounted) `presale_price` is used, after that `price`.
+    /// During the presale only the accounts on the presale allowlist of the song can buy
+    Presale { presale_end: U64, presale_price: SalePriceInYoctoNear, price: SalePriceInYoctoNear },
+}
+
+impl PriceSchedule {
+    /// Panics if the schedule does not make sense
+    pub fn assert_valid(&self) {
+        match self {
+            PriceSchedule::Fixed { .. } => {}
+            PriceSchedule::Stepped { steps } => {
+                assert!(!steps.is_empty(), "The price schedule needs at least one step!");
+                assert!(
+                    steps.windows(2).all(|pair| pair[0].from.0 < pair[1].from.0),
+                    "The steps of the price schedule have to be in increasing order!"
+                );
+            }
+            PriceSchedule::DutchAuction { start, end, start_price, end_price } => {
+                assert!(start.0 < end.0, "The end of the auction has to be after the start!");
+                assert!(start_price.0 >= end_price.0, "The price of a dutch auction can not go up!");
+            }
+            PriceSchedule::Presale { presale_price, price, .. } => {
+                assert!(presale_price.0 <= price.0, "The presale price can not be more than the price!");
+            }
+        }
+    }
+
+    /// The price at `now`
+    pub fn price_at(&self, now: u64) -> Balance {
+        match self {
+            PriceSchedule::Fixed { price } => price.0,
+            PriceSchedule::Stepped { steps } => steps
+                .iter()
+                .rev()
+                .find(|step| step.from.0 <= now)
+                .unwrap_or(&steps[0])
+                .price.0,
+            PriceSchedule::DutchAuction { start, end, start_price, end_price } => {
+                if now <= start.0 {
+                    start_price.0
+                } else if now >= end.0 {
+                    end_price.0
+                } else {
+                    let elapsed = (now - start.0) as u128;
+                    let duration = (end.0 - start.0) as u128;
+                    let difference = start_price.0 - end_price.0;
+                    let discount = difference / duration * elapsed + difference % duration * elapsed / duration;   // difference * elapsed could overflow
+                    start_price.0 - discount
+                }
+            }
+            PriceSchedule::Presale { presale_end, presale_price, price } => {
+                if now < presale_end.0 { presale_price.0 } else { price.0 }
+            }
+        }
+    }
+}
+
+/// Result of the PayoutRevenue proposal for a single song
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayoutOutcome {
+    /// The balances were split, and the transfers were added to the payout queue. `amount` is the $NEAR balance that was paid out,
+    /// `ft_amounts` are the NEP-141 balances that were paid out (token contract, amount)
+    Paid { amount: U128, ft_amounts: Vec<(AccountId, U128)> },
+    /// The song does not exist, or it does not have a RevenueTable yet
+    Skipped,
+    /// The caller is neither the owner of the song, nor a Council member
+    NotAuthorized,
+    /// There was nothing to pay out
+    ZeroBalance,
+    /// The balances could not be split (for example a payee group of the RevenueTable is nested too deep), the balances stay in the IncomeTable
+    SplitFailed { reason: String },
+}
+
+/// Progress of a PayoutRevenue proposal. If the gas ran out, the payout can be continued with `continue_payout_revenue()`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutProgress {
+    pub executor: AccountId,                                    // The account that executed the proposal, authorization is checked for this account
+    pub is_admin: bool,                                         // The executor was a Council member
+    pub cursor: u64,                                            // Position in `tree_index_list`, where the payout continues
+    pub results: Vec<(TreeIndex, PayoutOutcome)>,
+    pub finished: bool,
+}
+
+/// Platform fee of the DAO, and the fees that were collected, in yoctoNEAR, and in NEP-141 tokens (token contract -> amount)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlatformFees {
+    pub fee: u32,                                               // Current fee, in basis points
+    pub balance: U128,                                          // Fees that can be withdrawn with a WithdrawPlatformFees proposal
+    pub total: U128,                                            // Every fee that was ever collected
+    pub ft_balances: HashMap<AccountId, U128>,                  // Same as `balance`, for the sales in NEP-141 tokens
+    pub ft_total: HashMap<AccountId, U128>,                     // Same as `total`, for the sales in NEP-141 tokens
+}
+
+/// Result of `get_treasury_reconciliation()`, every amount is in yoctoNEAR.
+/// `contract_balance` = `storage_cost` + `locked_amount` + `song_balances` + `claimable` + `queued_payouts` + `failed_transactions` + `platform_fees`
+/// + `draft_storage` + `pending_purchases` + `unallocated`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryReconciliation {
+    pub contract_balance: U128,
+    pub storage_cost: U128,                                     // Balance that is staked for the storage of the contract
+    pub locked_amount: U128,                                    // Proposal and bounty bonds
+    pub song_balances: U128,                                    // Sum of the `current_balance` of the songs, waiting for payout
+    pub claimable: U128,                                        // Sum of the claimable revenue of the beneficiaries
+    pub queued_payouts: U128,                                   // $NEAR transfers in the payout queue
+    pub failed_transactions: U128,                              // Pending $NEAR FailedTransactions
+    pub platform_fees: U128,                                    // Platform fees that were not withdrawn yet
+    pub draft_storage: U128,                                    // Prepaid draft storage of the Artists, it can be withdrawn any time
+    pub pending_purchases: U128,                                // Price + storage deposit of the purchases that are waiting for the callback
+    pub unallocated: U128,                                      // Funds of the DAO itself (donations, storage paid by the DAO...)
+    pub is_covered: bool,                                       // False if the contract balance is less than what it owes
+}
+
+/// A transfer of a payout that is waiting in the payout queue. See `process_payout_queue()`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutTransfer {
+    pub beneficiary: AccountId,
+    pub amount: U128,
+    pub token_id: Option<AccountId>,                            // None for $NEAR
+    pub tree_index: TreeIndex,                                  // The song that was paid out
+}
+
+/// Automatic payout rule of a song, used by `distribute_due_revenue()`. The payout is due if any of the set conditions is met.
+/// It only pays out the balances of the song: the NEP-141 revenue, and the $NEAR revenue that could not be split when it arrived (there was no RevenueTable yet).
+/// $NEAR sales are credited to the claimable revenue of the beneficiaries right away, they don't count towards the threshold.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AutoPayout {
+    pub threshold: Option<U128>,                                // The $NEAR balance of the song (`current_balance`) reached this amount. NEP-141 balances are paid out by the period
+    pub period: Option<U64>,                                    // This much time (in nanoseconds) passed since the last payout, and there is something to pay out
+}
+
+/// Sale window and editions cap of a song. Every field is optional, None means there is no limit.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleLimits {
+    /// The NFT can't be bought before this timestamp
+    pub start: Option<U64>,
+    /// The NFT can't be bought after this timestamp
+    pub end: Option<U64>,
+    /// Maximum number of NFTs that can be sold
+    pub max_editions: Option<u64>,
+}
+
+impl IncomeTable {
+    /// True if the presale is going on at `now`, only the accounts on the allowlist can buy
+    pub fn in_presale(&self, now: u64) -> bool {
+        matches!(&self.price_schedule, Some(PriceSchedule::Presale { presale_end, .. }) if now < presale_end.0)
+    }
+
+    /// The price at `now`, calculated from the price schedule, if there is one. None if the NFT doesn't have a price yet
+    pub fn current_price(&self, now: u64) -> Option<Balance> {
+        match &self.price_schedule {
+            Some(schedule) => Some(schedule.price_at(now)),
+            None => self.price.map(|price| price.0),
+        }
+    }
+
+    /// Number of NFTs that can still be bought, None if there is no cap
+    pub fn remaining_editions(&self) -> Option<u64> {
+        self.sale_limits.max_editions.map(|max| max.saturating_sub(self.sold + self.pending_sales))
+    }
+
+    /// Panics if the NFT can't be bought at `now`, because of the sale window or the editions cap
+    pub fn assert_on_sale(&self, now: u64) {
+        if let Some(start) = self.sale_limits.start {
+            assert!(now >= start.0, "The sale has not started yet!");
+        }
+        if let Some(end) = self.sale_limits.end {
+            assert!(now < end.0, "The sale has ended!");
+        }
+        assert!(self.remaining_editions() != Some(0), "The NFT is sold out!");
+    }
+
+    /// True if there is $NEAR or NEP-141 revenue that was not paid out yet
+    pub fn has_balance(&self) -> bool {
+        self.current_balance > 0 || self.ft_balances.values().any(|balance| *balance > 0)
+    }
+
+    /// True if the AutoPayout rule of the song says that it should be paid out at `now`. The threshold is checked against `current_balance` only
+    pub fn is_payout_due(&self, now: u64) -> bool {
+        let rule = match &self.auto_payout {
+            Some(rule) => rule,
+            None => return false,
+        };
+        let threshold_reached = rule.threshold.map_or(false, |threshold| self.current_balance >= threshold.0);
+        let period_passed = rule.period.map_or(false, |period| now >= self.last_payout.saturating_add(period.0));
+
+        self.has_balance() && (threshold_reached || period_passed)
+    }
 }
 
 /// Payout object
@@ -222,12 +573,70 @@ pub struct Payout {
     pub payout: HashMap<AccountId, U128>,
 } 
 
-/// Failed Transaction object
+/// Failed Transaction object. The record is kept after it was settled or written off, for the audit history.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FailedTransaction {
     pub beneficiary: AccountId,
-    pub amount: Balance
+    pub amount: Balance,
+    pub token_id: Option<AccountId>,                                // None for $NEAR, otherwise the NEP-141 token contract
+    pub status: FailedTransactionStatus,
+    pub history: Vec<FailedTransactionEvent>,                       // Every status change, the first one is the failure itself
+}
+
+/// Lifecycle of a FailedTransaction
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum FailedTransactionStatus {
+    /// The transfer failed, the amount is still owed to the beneficiary
+    Pending,
+    /// The transfer is being sent again. It becomes Settled, or Pending again if it fails
+    Retried,
+    /// The transfer was successful
+    Settled,
+    /// The DAO decided not to send the amount
+    WrittenOff,
+}
+
+/// One status change of a FailedTransaction
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedTransactionEvent {
+    pub status: FailedTransactionStatus,
+    pub receiver: Option<AccountId>,                                // Where the transfer was sent, for Pending, Retried and Settled
+    pub timestamp: u64,
+}
+
+impl FailedTransaction {
+    /// New Pending record, for a transfer that failed now
+    pub fn new(beneficiary: AccountId, amount: Balance, token_id: Option<AccountId>) -> Self {
+        FailedTransaction {
+            history: vec![FailedTransactionEvent {
+                status: FailedTransactionStatus::Pending,
+                receiver: Some(beneficiary.clone()),
+                timestamp: env::block_timestamp(),
+            }],
+            beneficiary: beneficiary,
+            amount: amount,
+            token_id: token_id,
+            status: FailedTransactionStatus::Pending,
+        }
+    }
+
+    /// Changes the status, and adds the change to the history
+    pub fn set_status(&mut self, status: FailedTransactionStatus, receiver: Option<AccountId>) {
+        self.status = status;
+        self.history.push(FailedTransactionEvent {
+            status: status,
+            receiver: receiver,
+            timestamp: env::block_timestamp(),
+        });
+    }
+
+    /// True if the amount is still owed to the beneficiary (it is Pending, or it is being Retried)
+    pub fn is_open(&self) -> bool {
+        self.status == FailedTransactionStatus::Pending || self.status == FailedTransactionStatus::Retried
+    }
 }
 
 /// Return value of `mint_root`, from Fono-Root minting contract
@@ -238,6 +647,151 @@ pub struct MintRootResult {
     pub root_id: TokenId,                                           // ID of the RootNFT
 }
 
+/// Return value of `buy_nft_from_vault`, from Fono-Root minting contract.
+/// Older minting contracts only return if the NFT was moved. Newer ones also report the storage that was used
+/// (and send the rest of the storage deposit back to the DAO), and the ID of the NFT that the buyer got.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum VaultResult {
+    Moved(bool),
+    Detailed { success: bool, storage_used: Option<U128>, token_id: Option<TokenId> },
+}
+
+impl VaultResult {
+    /// True if the NFT was moved to the buyer
+    pub fn success(&self) -> bool {
+        match self {
+            VaultResult::Moved(success) => *success,
+            VaultResult::Detailed { success, .. } => *success,
+        }
+    }
+
+    /// Storage used by the minting contract, if it was reported
+    pub fn storage_used(&self) -> Option<Balance> {
+        match self {
+            VaultResult::Moved(_) => None,
+            VaultResult::Detailed { storage_used, .. } => storage_used.map(|used| used.0),
+        }
+    }
+
+    /// ID of the NFT that the buyer got, if it was reported
+    pub fn token_id(&self) -> Option<TokenId> {
+        match self {
+            VaultResult::Moved(_) => None,
+            VaultResult::Detailed { token_id, .. } => token_id.clone(),
+        }
+    }
+}
+
+impl From<bool> for VaultResult {
+    fn from(success: bool) -> Self {
+        VaultResult::Moved(success)
+    }
+}
+
+/// A successful sale, element of the sales log of the song
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Sale {
+    /// ID of the purchase, see `get_purchase_storage()`
+    pub purchase_id: u64,
+    pub buyer: AccountId,
+    /// Price in yoctoNEAR, or in the NEP-141 token
+    pub price: U128,
+    pub token_id: Option<AccountId>,                                // None for $NEAR, otherwise the NEP-141 token contract
+    /// ID of the NFT that the buyer got, if the minting contract reported it
+    pub nft_id: Option<TokenId>,
+    /// Timestamp, env::block_timestamp()
+    pub timestamp: u64,
+}
+
+/// Storage accounting of a single purchase. The storage deposit is attached to `buy_nft_from_vault`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PurchaseStorage {
+    pub tree_index: TreeIndex,
+    /// Storage deposit that was attached to `buy_nft_from_vault`
+    pub deposit: Balance,
+    /// Part of the deposit that was paid by the buyer. For purchases with NEP-141 tokens the DAO pays for the storage
+    pub paid_by_buyer: Balance,
+    /// Storage used by the minting contract. None until the callback. If the minting contract didn't report it, the whole deposit is counted as used
+    pub used: Option<Balance>,
+    /// Unused storage deposit that was sent back to the buyer
+    pub refunded: Balance,
+    /// Storage cost that was paid from the balance of the DAO
+    pub spent_by_dao: Balance,
+}
+
+impl SplitRules {
+    /// Panics if the tiers or the thresholds are not valid. `max_length` is the maximum length of the RevenueTables
+    pub fn assert_valid(&self, max_length: u32) {
+        let mut tier_beneficiaries: Vec<&AccountId> = Vec::new();
+        for tier in self.tiers.iter() {
+            assert!(tier.amount.0 > 0, "The amount of a fixed tier has to be more than 0!");
+            if let Some(max_share) = tier.max_share {
+                assert!(max_share > 0 && max_share <= 10_000, "The max share of a fixed tier has to be between 1 and 10000 basis points!");
+            }
+            assert!(!tier_beneficiaries.contains(&&tier.beneficiary), "An account can only have one fixed tier!");
+            tier_beneficiaries.push(&tier.beneficiary);
+        }
+
+        let mut last_threshold: Balance = 0;
+        for threshold in self.thresholds.iter() {
+            assert!(threshold.threshold.0 > last_threshold, "The thresholds have to be in increasing order, and more than 0!");
+            assert!(RevenueTable::new(threshold.revenue_table.0.clone(), max_length).is_some(), "The RevenueTable of a threshold is not valid!");
+            last_threshold = threshold.threshold.0;
+        }
+    }
+
+    /// The table that is used at `position` (cumulative split revenue of the song)
+    fn table_at<'a>(&'a self, base: &'a RevenueTable, position: Balance) -> &'a RevenueTable {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|threshold| threshold.threshold.0 <= position)
+            .map(|threshold| &threshold.revenue_table)
+            .unwrap_or(base)
+    }
+
+    /// Splits `amount`. `paid` is the revenue that was split before, `recouped` is what the fixed tiers received so far, it is updated.
+    /// The parts add up to `amount`: the rounding remainder of every segment goes to the largest share of its table (see `RevenueTable::split()`).
+    pub fn split(&self, base: &RevenueTable, amount: Balance, paid: Balance, recouped: &mut HashMap<AccountId, Balance>) -> HashMap<AccountId, Balance> {
+        let mut parts: HashMap<AccountId, Balance> = HashMap::new();
+        let mut remaining = amount;
+
+        for tier in self.tiers.iter() {
+            let already_recouped = recouped.get(&tier.beneficiary).cloned().unwrap_or(0);
+            let cap = match tier.max_share {
+                Some(max_share) => amount * max_share as u128 / 10_000u128,
+                None => remaining,
+            };
+            let part = tier.amount.0.saturating_sub(already_recouped).min(cap).min(remaining);
+            if part == 0 {
+                continue;
+            }
+            *parts.entry(tier.beneficiary.clone()).or_insert(0) += part;
+            recouped.insert(tier.beneficiary.clone(), already_recouped + part);
+            remaining = remaining - part;
+        }
+
+        let mut position = paid + (amount - remaining);                                         // Fixed tiers count towards the thresholds as well
+        while remaining > 0 {
+            let table = self.table_at(base, position);
+            let segment = match self.thresholds.iter().find(|threshold| threshold.threshold.0 > position) {
+                Some(next) => remaining.min(next.threshold.0 - position),                           // The amount that crosses a threshold is split in two parts
+                None => remaining,
+            };
+            for (beneficiary, part) in table.split(segment) {
+                *parts.entry(beneficiary).or_insert(0) += part;
+            }
+            position = position + segment;
+            remaining = remaining - segment;
+        }
+
+        parts
+    }
+}
 
 impl Action {
     pub fn to_policy_label(&self) -> String {
@@ -246,18 +800,35 @@ impl Action {
 }
 
 impl RevenueTable {
-    pub fn new(map: HashMap<AccountId, u64>) -> Option<Self> {
+    /// `max_length` is the `max_revenue_table_length` of the Policy
+    pub fn new(map: HashMap<AccountId, u64>, max_length: u32) -> Option<Self> {
         let sum: u64 = map.values().sum();
-        let max_length = 16;
 
-        // RevenueTable is only valid if total values adds up to 100%, and length of the list is below limit
-        if sum == 10000 && map.len() < max_length {
+        // RevenueTable is only valid if total values adds up to 100%, and length of the list is not above the limit
+        if sum == 10000 && map.len() as u32 <= max_length {
             Some(RevenueTable(map))
         } else {
             log!("Revenue Table is not valid!");
             None
         }
     }
+
+    /// Splits `amount` according to the percentages. The rounding remainder goes to the beneficiary with the largest share
+    /// (the smallest AccountId, if more beneficiaries have the same share), so the parts always add up to `amount`.
+    pub fn split(&self, amount: Balance) -> HashMap<AccountId, Balance> {
+        let mut parts: HashMap<AccountId, Balance> = self.0.iter()
+            .map(|(beneficiary, percent)| (beneficiary.clone(), *percent as u128 * amount / 10_000u128))
+            .collect();
+
+        let remainder = amount - parts.values().sum::<Balance>();
+        if remainder > 0 {
+            let (largest, _) = self.0.iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .unwrap();
+            *parts.get_mut(largest).unwrap() += remainder;
+        }
+        parts
+    }
 }
 impl IntoIterator for RevenueTable {
     type Item = (AccountId, u64);
@@ -275,12 +846,155 @@ impl Deref for RevenueTable {
     }
 }
 
+/// Problem with a field of an InProgressNft (draft), because of which it can not be minted
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DraftError {
+    /// The field is needed for minting, but it is not filled
+    Missing { field: String },
+    /// The field is not a valid IPFS CID (CIDv0 or base32 CIDv1)
+    InvalidCid { field: String },
+    /// The field is not a valid http(s):// or ipfs:// URL
+    InvalidUrl { field: String },
+    /// The hash is not a SHA256 hash (HASH_LENGTH bytes)
+    InvalidHashLength { field: String, length: u64 },
+    /// The text is longer than the limit
+    TooLong { field: String, length: u64, max_length: u64 },
+}
+
+impl InProgressMetadata {
+    /// Applies the patch to the draft. Fields that are not in the patch keep their old value.
+    pub fn apply_patch(&mut self, patch: &NftDataPatch) {
+        fn merge<T: Clone>(field: &mut Option<T>, patched: &Option<Option<T>>) {
+            if let Some(value) = patched {
+                *field = value.clone();
+            }
+        }
+
+        if let Some(contract) = &patch.contract {
+            self.contract = contract.clone();
+        }
+        merge(&mut self.title, &patch.title);
+        merge(&mut self.desc, &patch.desc);
+        merge(&mut self.image, &patch.image_cid);
+        merge(&mut self.image_hash, &patch.image_hash);
+        merge(&mut self.music, &patch.music_folder_cid);
+        merge(&mut self.music_hash, &patch.music_folder_hash);
+        merge(&mut self.animation_url, &patch.animation_url);
+        merge(&mut self.animation_url_hash, &patch.animation_url_hash);
+        merge(&mut self.meta, &patch.meta_json_cid);
+        merge(&mut self.meta_hash, &patch.meta_json_hash);
+    }
+
+    /// Panics if there is a CID without its hash
+    pub fn assert_hashes_exist(&self) {
+        if self.image.is_some() {
+            assert!(self.image_hash.is_some(), "Hash has to exist, if image exists!");
+        }
+        if self.music.is_some() {
+            assert!(self.music_hash.is_some(), "Hash has to exist, if music folder exists!");
+        }
+        if self.animation_url.is_some() {
+            assert!(self.animation_url_hash.is_some(), "Hash has to exist, if music exists!");
+        }
+        if self.meta.is_some() {
+            assert!(self.meta_hash.is_some(), "Hash has to exist, if meta exists!");
+        }
+    }
+
+    /// Returns true if every field that is needed for minting is filled, and the fields are valid
+    pub fn is_ready(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    /// Returns the list of problems, because of which the draft can not be minted. Empty list means the draft can be minted
+    pub fn validate(&self) -> Vec<DraftError> {
+        let mut errors: Vec<DraftError> = self.missing_fields()
+            .into_iter()
+            .map(|field| DraftError::Missing { field })
+            .collect();
+
+        let cid_regex = Regex::new(r"^(Qm[1-9A-HJ-NP-Za-km-z]{44}|b[a-z2-7]{58,})$").unwrap();
+        let url_regex = Regex::new(r"^(https?|ipfs)://\S+$").unwrap();
+
+        let texts = [("title", &self.title, MAX_TITLE_LENGTH), ("desc", &self.desc, MAX_DESC_LENGTH)];
+        for (field, value, max_length) in texts.iter() {
+            if let Some(text) = value {
+                if text.len() > *max_length {
+                    errors.push(DraftError::TooLong { field: field.to_string(), length: text.len() as u64, max_length: *max_length as u64 });
+                }
+            }
+        }
+
+        let cids = [("image", &self.image), ("music", &self.music), ("meta", &self.meta)];
+        for (field, value) in cids.iter() {
+            if let Some(cid) = value {
+                if !cid_regex.is_match(cid) {
+                    errors.push(DraftError::InvalidCid { field: field.to_string() });
+                }
+            }
+        }
+
+        if let Some(url) = &self.animation_url {
+            if !url_regex.is_match(url) {
+                errors.push(DraftError::InvalidUrl { field: "animation_url".to_string() });
+            }
+        }
+
+        let hashes = [
+            ("image_hash", &self.image_hash),
+            ("music_hash", &self.music_hash),
+            ("animation_url_hash", &self.animation_url_hash),
+            ("meta_hash", &self.meta_hash),
+        ];
+        for (field, value) in hashes.iter() {
+            if let Some(hash) = value {
+                if hash.0.len() != HASH_LENGTH {
+                    errors.push(DraftError::InvalidHashLength { field: field.to_string(), length: hash.0.len() as u64 });
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Names of the fields that are needed for minting, but are not filled yet (MintRoot would fail on these)
+    pub fn missing_fields(&self) -> Vec<String> {
+        let required = [
+            ("title", self.title.is_some()),
+            ("desc", self.desc.is_some()),
+            ("image", self.image.is_some()),
+            ("image_hash", self.image_hash.is_some()),
+            ("music", self.music.is_some()),
+            ("music_hash", self.music_hash.is_some()),
+            ("animation_url", self.animation_url.is_some()),
+            ("animation_url_hash", self.animation_url_hash.is_some()),
+            ("meta", self.meta.is_some()),
+            ("meta_hash", self.meta_hash.is_some()),
+        ];
+
+        required.iter()
+            .filter(|(_, filled)| !filled)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+}
+
 impl UniqId {
     pub fn new(contract: AccountId, root_id: TokenId) -> UniqId {
+        UniqId::try_new(contract, root_id).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `new()`, but returns the error instead of panicking. Used in `mint_root_callback`
+    pub fn try_new(contract: AccountId, root_id: TokenId) -> Result<UniqId, String> {
         let root_id_regex = Regex::new(r"fono-root-[0-9]{1,}").unwrap();
-        assert!(root_id_regex.is_match(&root_id), "RootID is not valid!");
-        assert!(contract.to_string().parse::<AccountId>().is_ok(), "AccountId is not valid!");
-        UniqId(format!("{}-{}", contract, root_id))
+        if !root_id_regex.is_match(&root_id) {
+            return Err("RootID is not valid!".to_string());
+        }
+        if contract.to_string().parse::<AccountId>().is_err() {
+            return Err("AccountId is not valid!".to_string());
+        }
+        Ok(UniqId(format!("{}-{}", contract, root_id)))
     }
 }
 
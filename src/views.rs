@@ -13,6 +13,12 @@ pub struct ProposalOutput {
     pub id: u64,
     #[serde(flatten)]
     pub proposal: Proposal,
+    /// Reason of the failure, if the proposal is Failed because of a FonoRoot action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    /// Progress and per-song results, if this is an executed PayoutRevenue proposal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_progress: Option<PayoutProgress>,
 }
 
 /// This is format of output via JSON for the bounty.
@@ -25,6 +31,47 @@ pub struct BountyOutput {
     pub bounty: Bounty,
 }
 
+/// This is format of output via JSON for the InProgressNfts (drafts).
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DraftOutput {
+    #[serde(flatten)]
+    pub draft: InProgressMetadata,
+    /// True if the draft can be minted. If it is false, `validate_draft()` will tell the reason
+    pub ready: bool,
+    /// Fields that have to be filled before the draft can be minted
+    pub missing_fields: Vec<String>,
+}
+
+impl From<InProgressMetadata> for DraftOutput {
+    fn from(draft: InProgressMetadata) -> Self {
+        let missing_fields = draft.missing_fields();
+        DraftOutput {
+            ready: draft.is_ready(),
+            draft: draft,
+            missing_fields: missing_fields,
+        }
+    }
+}
+
+/// This is format of output via JSON for the sale status of a song.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleStatus {
+    /// True if the NFT can be bought right now
+    pub on_sale: bool,
+    #[serde(flatten)]
+    pub limits: SaleLimits,
+    /// Number of NFTs sold
+    pub sold: u64,
+    /// Number of purchases in progress
+    pub pending_sales: u64,
+    /// Number of NFTs that can still be bought, None if there is no cap
+    pub remaining: Option<u64>,
+    /// True if the presale is going on, only accounts on the presale allowlist can buy
+    pub presale: bool,
+}
+
 #[near_bindgen]
 impl Contract {
     /// Returns semver of this contract.
@@ -88,18 +135,22 @@ impl Contract {
 
     /// Get proposals in paginated view.
     pub fn get_proposals(&self, from_index: u64, limit: u64) -> Vec<ProposalOutput> {
+        self.assert_not_migrating();
         (from_index..min(self.last_proposal_id, from_index + limit))
             .filter_map(|id| {
                 self.proposals.get(&id).map(|proposal| ProposalOutput {
                     id,
                     proposal: proposal.into(),
+                    failure_reason: self.proposal_failures.get(&id),
+                    payout_progress: self.payout_progress.get(&id),
                 })
             })
             .collect()
     }
 
-    /// List all InProgressNfts
+    /// List all InProgressNfts. This will run out of gas when there are a lot of drafts, use the paginated views instead
     pub fn get_in_progress_nfts(&self) -> Vec<InProgressMetadata> {
+        self.assert_not_migrating();
         (0..self.in_progress_nonce)
             .filter_map(|id| {
                 self.in_progress_nfts.get(&id)
@@ -107,14 +158,51 @@ impl Contract {
             .collect()
     }
 
+    /// List the InProgressNfts of an Artist, in paginated form
+    pub fn get_drafts_by_artist(&self, artist: AccountId, from_index: u64, limit: u64) -> Vec<DraftOutput> {
+        self.assert_not_migrating();
+        self.internal_get_drafts(self.drafts_by_artist.get(&artist), from_index, limit)
+    }
+
+    /// List the InProgressNfts that will be minted on the minting contract, in paginated form
+    pub fn get_drafts_by_contract(&self, contract: AccountId, from_index: u64, limit: u64) -> Vec<DraftOutput> {
+        self.assert_not_migrating();
+        self.internal_get_drafts(self.drafts_by_contract.get(&contract), from_index, limit)
+    }
+
+    /// Returns the list of problems, because of which the InProgressNft can not be minted. Empty list means it can be minted
+    pub fn validate_draft(&self, id: u64) -> Vec<DraftError> {
+        self.assert_not_migrating();
+        self.in_progress_nfts.get(&id).expect("Draft does not exist!").validate()
+    }
+
+    /// Get a single InProgressNft, with the readiness flag
+    pub fn get_draft(&self, id: u64) -> Option<DraftOutput> {
+        self.assert_not_migrating();
+        self.in_progress_nfts.get(&id).map(DraftOutput::from)
+    }
+
+    /// List InProgressNfts that are scheduled to be minted, in the order of the release time
+    pub fn get_scheduled_mints(&self, from_index: u64, limit: u64) -> Vec<InProgressMetadata> {
+        self.assert_not_migrating();
+        self.scheduled_mints
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|((_, id), _)| self.in_progress_nfts.get(&id))
+            .collect()
+    }
+
     /// List CatalogueEntries for an artist's catalogue
     pub fn get_catalogue(&self, artist: AccountId) -> Vec<(TreeIndex, Option<CatalogueEntry>)> {
+        self.assert_not_migrating();
         let catalogue_for_artist = self.catalogues.get(&artist).unwrap();
         catalogue_for_artist.to_vec()
     }
 
     /// Get slice of the Catalogue of an Artist
     pub fn get_catalogue_slice(&self, artist: AccountId, from_index: u64, limit: u64) -> Vec<(TreeIndex, Option<CatalogueEntry>)> {
+        self.assert_not_migrating();
         let catalogue_for_artist = self.catalogues.get(&artist).unwrap();
         let catalogue_as_vec = catalogue_for_artist.to_vec();
 
@@ -126,6 +214,7 @@ impl Contract {
 
     /// Get specific Catalogue entries for Artist
     pub fn get_catalogue_entries(&self, artist: AccountId, list: Vec<TreeIndex>) -> Vec<(TreeIndex, Option<CatalogueEntry>)> {
+        self.assert_not_migrating();
         let catalogue_for_artist = self.catalogues.get(&artist).unwrap();
         let mut result_vec = Vec::new();
 
@@ -141,22 +230,204 @@ impl Contract {
 
     /// Get a single IncomeTable
     pub fn get_single_income_table(&self, id: TreeIndex) -> IncomeTable {
+        self.assert_not_migrating();
         self.income_tables.get(&id).unwrap()
     }
 
-    /// Get price for single NFT
+    /// Get price for single NFT. If the song has a price schedule, this is the current price
     pub fn get_price(&self, minting_contract: AccountId, root_id: TokenId) -> Option<SalePriceInYoctoNear> {
+        self.assert_not_migrating();
         let uniq_id = UniqId::new(minting_contract, root_id);
         let tree_index = self.uniq_id_to_tree_index.get(&uniq_id.clone()).unwrap();
         
         self.income_tables.get(&tree_index)
         .unwrap()
-        .price
+        .current_price(env::block_timestamp())
+        .map(U128)
+    }
+
+    /// Get price for single NFT in a NEP-141 token
+    pub fn get_ft_price(&self, minting_contract: AccountId, root_id: TokenId, token_id: AccountId) -> Option<U128> {
+        self.assert_not_migrating();
+        let uniq_id = UniqId::new(minting_contract, root_id);
+        let tree_index = self.uniq_id_to_tree_index.get(&uniq_id.clone()).unwrap();
+
+        self.income_tables.get(&tree_index)
+        .unwrap()
+        .ft_prices
+        .get(&token_id)
+        .cloned()
+    }
+
+    /// NEP-199 compatible payout for a secondary sale of the song with `tree_index`, for `balance` yoctoNEAR.
+    /// The royalty of the song is split according to the RevenueTable, `owner_id` (the seller) gets the rest.
+    /// If the song does not have a RevenueTable or a royalty yet, everything goes to the seller.
+    pub fn nft_payout(&self, tree_index: TreeIndex, owner_id: AccountId, balance: U128, max_len_payout: u32) -> Payout {
+        self.assert_not_migrating();
+        let income_table = self.income_tables.get(&tree_index).expect("The song does not exist!");
+        let entry = self.catalogues.get(&income_table.owner).and_then(|catalogue| catalogue.get(&tree_index)).flatten();
+
+        let royalty = balance.0 * income_table.royalty as u128 / 10_000u128;
+        let mut payout_object = match entry {
+            Some(entry) if royalty > 0 => self.generate_payout_object(entry.revenue_table, royalty, max_len_payout),
+            _ => Payout { payout: HashMap::new() },
+        };
+
+        let royalties: Balance = payout_object.payout.values().map(|amount| amount.0).sum();
+        let rest = payout_object.payout.entry(owner_id).or_insert(U128(0));                // The seller can be a beneficiary as well
+        rest.0 = rest.0 + balance.0 - royalties;                                              // Rounding leftover goes to the seller
+        assert!(payout_object.payout.len() as u32 <= max_len_payout, "The contract cannot payout to that many receivers");
+
+        payout_object
+    }
+
+    /// Sale window, editions cap and remaining supply of a song
+    pub fn get_sale_status(&self, tree_index: TreeIndex) -> SaleStatus {
+        self.assert_not_migrating();
+        let income_table = self.income_tables.get(&tree_index).expect("The song does not exist!");
+        let now = env::block_timestamp();
+        let on_sale = income_table.current_price(now).is_some()
+            && income_table.sale_limits.start.map_or(true, |start| now >= start.0)
+            && income_table.sale_limits.end.map_or(true, |end| now < end.0)
+            && income_table.remaining_editions() != Some(0);
+
+        SaleStatus {
+            on_sale: on_sale,
+            remaining: income_table.remaining_editions(),
+            presale: income_table.in_presale(now),
+            limits: income_table.sale_limits,
+            sold: income_table.sold,
+            pending_sales: income_table.pending_sales,
+        }
+    }
+
+    /// Accounts on the presale allowlist of the song, in paginated form
+    pub fn get_presale_allowlist(&self, tree_index: TreeIndex, from_index: u64, limit: u64) -> Vec<AccountId> {
+        match self.presale_allowlists.get(&tree_index) {
+            Some(allowlist) => allowlist.iter().skip(from_index as usize).take(limit as usize).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// True if the account can buy the song during the presale
+    pub fn is_on_presale_allowlist(&self, tree_index: TreeIndex, account_id: AccountId) -> bool {
+        self.presale_allowlists.get(&tree_index).map_or(false, |allowlist| allowlist.contains(&account_id))
+    }
+
+    /// Sales log of the song, in paginated form
+    pub fn get_sales(&self, tree_index: TreeIndex, from_index: u64, limit: u64) -> Vec<Sale> {
+        match self.sales.get(&tree_index) {
+            Some(sales) => sales.iter().skip(from_index as usize).take(limit as usize).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Purchases of the buyer, as (TreeIndex, Sale), in paginated form
+    pub fn get_purchases_by_buyer(&self, account_id: AccountId, from_index: u64, limit: u64) -> Vec<(TreeIndex, Sale)> {
+        let purchases = match self.purchases_by_buyer.get(&account_id) {
+            Some(purchases) => purchases,
+            None => return Vec::new(),
+        };
+
+        purchases
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|(tree_index, index)| {
+                self.sales.get(&tree_index).and_then(|sales| sales.get(index)).map(|sale| (tree_index, sale))
+            })
+            .collect()
+    }
+
+    /// Get a payee group by its ID
+    pub fn get_payee_group(&self, group_id: AccountId) -> Option<PayeeGroup> {
+        self.payee_groups.get(&group_id)
+    }
+
+    /// List the payee groups, in paginated form
+    pub fn get_payee_groups(&self, from_index: u64, limit: u64) -> Vec<(AccountId, PayeeGroup)> {
+        self.payee_groups
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Transfers in the payout queue that were not sent yet, with their position, in paginated form. `from_index` is relative to the start of the queue
+    pub fn get_payout_queue(&self, from_index: u64, limit: u64) -> Vec<(u64, PayoutTransfer)> {
+        let first = self.payout_queue_start + from_index;
+        let last = std::cmp::min(first.saturating_add(limit), self.payout_queue_end);
+        (first..last)
+            .map(|position| (position, self.payout_queue.get(&position).unwrap()))
+            .collect()
+    }
+
+    /// Platform fee of the DAO, the fees that can be withdrawn, and every fee that was collected
+    pub fn get_platform_fees(&self) -> PlatformFees {
+        PlatformFees {
+            fee: self.policy.get().unwrap().to_policy().platform_fee,
+            balance: U128(self.platform_fee_balance),
+            total: U128(self.total_platform_fees),
+            ft_balances: self.ft_platform_fee_balances.iter().map(|(token_id, balance)| (token_id.clone(), U128(*balance))).collect(),
+            ft_total: self.ft_total_platform_fees.iter().map(|(token_id, total)| (token_id.clone(), U128(*total))).collect(),
+        }
+    }
+
+    /// Shows what the $NEAR balance of the contract is made of: the storage, the bonds, the revenue that belongs to the beneficiaries,
+    /// and the deposits of the Artists and of the buyers.
+    /// The rest is `unallocated`. `is_covered` is false if the balance is less than what the contract owes.
+    /// It reads every IncomeTable and FailedTransaction, so it is only usable as a view call.
+    pub fn get_treasury_reconciliation(&self) -> TreasuryReconciliation {
+        self.assert_not_migrating();
+        let contract_balance = env::account_balance();
+        let storage_cost = env::storage_usage() as Balance * env::storage_byte_cost();
+        let song_balances: Balance = self.income_tables.iter().map(|(_, income_table)| income_table.current_balance).sum();
+        let failed_transactions: Balance = self.failed_transactions
+            .iter()
+            .filter(|(_, failed)| failed.token_id.is_none() && failed.status == FailedTransactionStatus::Pending)
+            .map(|(_, failed)| failed.amount)
+            .sum();
+
+        let allocated = storage_cost + self.locked_amount + song_balances + self.total_claimable + self.queued_revenue + failed_transactions + self.platform_fee_balance
+            + self.total_draft_storage_balance + self.pending_purchase_deposits;
+        TreasuryReconciliation {
+            contract_balance: U128(contract_balance),
+            storage_cost: U128(storage_cost),
+            locked_amount: U128(self.locked_amount),
+            song_balances: U128(song_balances),
+            claimable: U128(self.total_claimable),
+            queued_payouts: U128(self.queued_revenue),
+            failed_transactions: U128(failed_transactions),
+            platform_fees: U128(self.platform_fee_balance),
+            draft_storage: U128(self.total_draft_storage_balance),
+            pending_purchases: U128(self.pending_purchase_deposits),
+            unallocated: U128(contract_balance.saturating_sub(allocated)),
+            is_covered: contract_balance >= allocated,
+        }
+    }
+
+    /// Storage accounting of the purchases, in paginated form
+    pub fn get_purchase_storage(&self, from_index: u64, limit: u64) -> Vec<(u64, PurchaseStorage)> {
+        self.purchase_storage
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
     }
 
+    /// Sum of the storage costs of the purchases that were paid from the balance of the DAO
+    pub fn get_total_storage_spent_by_dao(&self) -> U128 {
+        U128(self.total_storage_spent_by_dao)
+    }
+
+    /// List the NEP-141 tokens that can be used for buying NFTs
+    pub fn get_whitelisted_tokens(&self) -> Vec<AccountId> {
+        self.ft_whitelist.to_vec()
+    }
 
     /// List income tables, with possible limit
     pub fn get_income_tables(&self, from_index: u64, limit: u64) -> Vec<(TreeIndex, IncomeTable)> {
+        self.assert_not_migrating();
         let end = from_index + limit;
         self.income_tables.range((Bound::Included(from_index), Bound::Excluded(end)))
             .collect::<Vec<(TreeIndex, IncomeTable)>>()
@@ -190,6 +461,7 @@ impl Contract {
 
     /// List income tables, with possible limit
     pub fn get_failed_transactions(&self, from_index: u64, limit: u64) -> Vec<(u64, FailedTransaction)> {
+        self.assert_not_migrating();
         let failed_transactions_as_vec = self.failed_transactions.to_vec();
 
         let start: usize = from_index as usize;
@@ -198,12 +470,34 @@ impl Contract {
         failed_transactions_as_vec[start .. end].to_vec()
     }
 
+    /// List the failed transactions that are still owed to `account_id` (Pending or Retried). Pending ones can be retried with `retry_failed_transaction()`
+    /// Only the open failed transactions of the account are read, see `open_failed_transactions`
+    pub fn get_failed_transactions_for(&self, account_id: AccountId) -> Vec<(u64, FailedTransaction)> {
+        self.assert_not_migrating();
+        match self.open_failed_transactions.get(&account_id) {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| self.failed_transactions.get(&id).map(|failed_transaction| (id, failed_transaction)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Get a failed transaction with its status and the full history of status changes
+    pub fn get_failed_transaction(&self, failed_id: u64) -> Option<FailedTransaction> {
+        self.assert_not_migrating();
+        self.failed_transactions.get(&failed_id)
+    }
+
     /// Get specific proposal.
     pub fn get_proposal(&self, id: u64) -> ProposalOutput {
+        self.assert_not_migrating();
         let proposal = self.proposals.get(&id).expect("ERR_NO_PROPOSAL");
         ProposalOutput {
             id,
             proposal: proposal.into(),
+            failure_reason: self.proposal_failures.get(&id),
+            payout_progress: self.payout_progress.get(&id),
         }
     }
 